@@ -0,0 +1,123 @@
+//! Times the per-pixel clip test of `Scissor` against the four comparisons it replaced, by
+//! writing every pixel of a full-screen buffer through a tight clip rect, and times painting a
+//! full-screen mesh clipped that way end to end.
+//!
+//! Run with `cargo run --release --example bench_scissor`. Both clip tests are timed in the same
+//! run, so the speedup it prints is the improvement of the single comparison per axis. The timing
+//! of the painted mesh has nothing to compare against within a run: to measure a change to the
+//! painter, run the example on the commit before it for the baseline, then on the change itself,
+//! on the same machine. Timings vary between machines, so report both outputs together.
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use egui::{epaint, pos2, vec2, ClippedPrimitive, Color32, Rect, TextureOptions, TexturesDelta};
+use egui_euc::Scissor;
+use euc::{Buffer2d, Target, Texture};
+
+const SIZE: [usize; 2] = [1920, 1080];
+/// A tight clip rect in the middle of the screen, as `[x, y, width, height]`.
+const CLIP: [usize; 4] = [900, 500, 120, 80];
+const RUNS: u32 = 20;
+
+/// `Scissor` as it was, testing each bound separately.
+struct FourComparisons<T> {
+    inner: T,
+    rect: [usize; 4],
+}
+
+impl<T: Texture<2>> Texture<2> for FourComparisons<T> {
+    type Index = T::Index;
+    type Texel = T::Texel;
+
+    fn size(&self) -> [Self::Index; 2] {
+        self.inner.size()
+    }
+
+    fn read(&self, index: [Self::Index; 2]) -> Self::Texel {
+        self.inner.read(index)
+    }
+}
+
+impl<T: Target> Target for FourComparisons<T> {
+    unsafe fn read_exclusive_unchecked(&self, x: usize, y: usize) -> Self::Texel {
+        unsafe { self.inner.read_exclusive_unchecked(x, y) }
+    }
+
+    unsafe fn write_exclusive_unchecked(&self, x: usize, y: usize, texel: Self::Texel) {
+        let [rx, ry, width, height] = self.rect;
+        if x >= rx && x < rx + width && y >= ry && y < ry + height {
+            unsafe { self.inner.write_exclusive_unchecked(x, y, texel) }
+        }
+    }
+}
+
+/// The fastest of `RUNS` runs of `run`.
+fn best_of(mut run: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+/// Writes every pixel of the screen through `target`, as a rasterizer drawing a full-screen
+/// mesh does.
+fn write_all(target: &impl Target<Texel = u32>) {
+    let [width, height] = SIZE;
+    for y in 0..height {
+        for x in 0..width {
+            // SAFETY: Within the buffer, and nothing else accesses it
+            unsafe { target.write_exclusive_unchecked(black_box(x), black_box(y), 0xffff_ffff) };
+        }
+    }
+}
+
+fn main() {
+    let [x, y, width, height] = CLIP;
+    let mut buffer = Buffer2d::fill(SIZE, 0u32);
+
+    let folded = best_of(|| write_all(&Scissor::new(&mut buffer, x, y, width, height)));
+    let separate = best_of(|| {
+        write_all(&FourComparisons {
+            inner: &mut buffer,
+            rect: CLIP,
+        })
+    });
+
+    // A full-screen rectangle clipped to the same rect, through the whole painter
+    let mut mesh = epaint::Mesh::default();
+    mesh.add_colored_rect(
+        Rect::from_min_size(pos2(0.0, 0.0), vec2(SIZE[0] as f32, SIZE[1] as f32)),
+        Color32::from_rgba_unmultiplied(200, 100, 50, 180),
+    );
+    let primitives = [ClippedPrimitive {
+        clip_rect: Rect::from_min_size(pos2(x as f32, y as f32), vec2(width as f32, height as f32)),
+        primitive: epaint::Primitive::Mesh(mesh),
+    }];
+    let white = egui::ColorImage::new([1, 1], vec![Color32::WHITE]);
+    let textures_delta = TexturesDelta {
+        set: vec![(
+            egui::TextureId::default(),
+            epaint::ImageDelta::full(white, TextureOptions::NEAREST),
+        )],
+        free: Vec::new(),
+    };
+    let mut painter = egui_euc::Painter::new();
+    painter.begin_frame(&textures_delta);
+    let painted = best_of(|| painter.paint(&mut buffer, &primitives, 1.0));
+    painter.end_frame();
+
+    let pixels = SIZE[0] * SIZE[1];
+    println!("{pixels} pixel writes clipped to {width}x{height}");
+    println!("one comparison per axis:  {folded:?}");
+    println!("two comparisons per axis: {separate:?}");
+    println!(
+        "speedup:                  {:.2}x",
+        separate.as_secs_f64() / folded.as_secs_f64()
+    );
+    println!("full-screen mesh painted through the clip rect: {painted:?}");
+}
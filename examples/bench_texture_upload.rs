@@ -0,0 +1,73 @@
+//! Times uploading 20 textures in one `TexturesDelta`, as on the first frame of a GUI with a font
+//! atlas and several images, against uploading them one per frame. With the `par` feature the
+//! batch is decoded in parallel, so compare a run with `--no-default-features` against one without.
+//!
+//! Run with `cargo run --release --example bench_texture_upload`.
+
+use std::time::{Duration, Instant};
+
+use egui::{epaint, Color32, ColorImage, TextureId, TextureOptions, TexturesDelta};
+
+const TEXTURES: u64 = 20;
+const SIZE: [usize; 2] = [512, 512];
+const RUNS: u32 = 10;
+
+fn delta(id: u64) -> (TextureId, epaint::ImageDelta) {
+    let pixels = (0..SIZE[0] * SIZE[1])
+        .map(|i| Color32::from_rgba_unmultiplied(i as u8, (i >> 8) as u8, id as u8, 200))
+        .collect();
+    let image = ColorImage::new(SIZE, pixels);
+    (
+        TextureId::User(id),
+        epaint::ImageDelta::full(image, TextureOptions::LINEAR),
+    )
+}
+
+/// The fastest of `RUNS` runs of `run`.
+fn best_of(mut run: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            run();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn main() {
+    let deltas: Vec<_> = (0..TEXTURES).map(delta).collect();
+
+    let batched = TexturesDelta {
+        set: deltas.clone(),
+        free: Vec::new(),
+    };
+    let batch = best_of(|| {
+        let mut painter = egui_euc::Painter::new();
+        painter.begin_frame(&batched);
+        painter.end_frame();
+    });
+
+    let frames: Vec<_> = deltas
+        .into_iter()
+        .map(|delta| TexturesDelta {
+            set: vec![delta],
+            free: Vec::new(),
+        })
+        .collect();
+    let one_by_one = best_of(|| {
+        let mut painter = egui_euc::Painter::new();
+        for frame in &frames {
+            painter.begin_frame(frame);
+            painter.end_frame();
+        }
+    });
+
+    println!("{TEXTURES} textures of {}x{} texels", SIZE[0], SIZE[1]);
+    println!("one delta:        {batch:?}");
+    println!("one per frame:    {one_by_one:?}");
+    println!(
+        "speedup:          {:.2}x",
+        one_by_one.as_secs_f64() / batch.as_secs_f64()
+    );
+}
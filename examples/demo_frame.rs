@@ -0,0 +1,15 @@
+//! Renders one frame of the egui demo headlessly with `render_demo` and prints a preview of it, as
+//! a rich scene for reproducing rendering bugs.
+//!
+//! Run with `cargo run --example demo_frame --features test-util`.
+
+fn main() {
+    let buffer = egui_euc::render_demo([1280, 800], 1.0);
+
+    let mut colors: Vec<u32> = buffer.raw().to_vec();
+    colors.sort_unstable();
+    colors.dedup();
+
+    egui_euc::debug_print_buffer(&buffer, 160);
+    println!("{} distinct colors", colors.len());
+}
@@ -14,23 +14,18 @@ fn main() -> anyhow::Result<()> {
 }
 
 struct App {
-    tex: egui::TextureId,
+    tex: egui::TextureHandle,
     sub: SoftwareGui,
     demo: egui_demo_lib::DemoWindows,
 }
 
 impl App {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let options = egui::TextureOptions::NEAREST;
-        let image = egui::ImageData::from(egui::ColorImage::filled(
-            [WIDTH, HEIGHT],
-            egui::Color32::RED,
-        ));
-        let tex = cc
-            .egui_ctx
-            .tex_manager()
-            .write()
-            .alloc("sub-gui".into(), image, options);
+        let tex = cc.egui_ctx.load_texture(
+            "sub-gui",
+            egui::ColorImage::filled([WIDTH, HEIGHT], egui::Color32::RED),
+            egui::TextureOptions::NEAREST,
+        );
 
         Self {
             demo: Default::default(),
@@ -51,7 +46,7 @@ impl eframe::App for App {
             );
 
             ui.painter().image(
-                self.tex,
+                self.tex.id(),
                 rect,
                 egui::Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
                 egui::Color32::WHITE,
@@ -63,26 +58,17 @@ impl eframe::App for App {
 
             raw_input.screen_rect = Some(egui::Rect::from_min_size(egui::Pos2::ZERO, Vec2::new(WIDTH as f32, HEIGHT as f32) / ui.pixels_per_point()));
 
-            for event in &mut raw_input.events {
-                match event {
-                    egui::Event::PointerMoved(pos) => {
-                        *pos -= rect.min.to_vec2();
-                    },
-                    egui::Event::PointerButton { pos, .. } => {
-                        *pos -= rect.min.to_vec2();
-                    }
-                    _ => (),
-                }
-            }
+            let sub_rect = raw_input.screen_rect.unwrap();
+            egui_euc::remap_raw_input(&mut raw_input, rect);
+            egui_euc::clip_pointer_events(&mut raw_input, sub_rect);
 
             //self.sub.egui_ctx.set_pixels_per_point(ui.pixels_per_point());
-            let new_image = self.sub.update(raw_input, [WIDTH, HEIGHT], |ctx| {
-                self.demo.ui(ctx);
-            });
-
-            ui.ctx().tex_manager().write().set(
-                self.tex,
-                egui::epaint::ImageDelta::full(new_image, egui::TextureOptions::NEAREST),
+            self.sub.update_texture(
+                &mut self.tex,
+                egui::TextureOptions::NEAREST,
+                raw_input,
+                [WIDTH, HEIGHT],
+                |ctx| self.demo.ui(ctx),
             );
         });
     }
@@ -0,0 +1,59 @@
+//! Temporal averaging of painted frames, e.g. for screenshots of animated UIs.
+
+use egui::Rgba;
+use euc::{Buffer2d, Target, Texture};
+
+use crate::{pack_pixel, unpack_pixel, Painter};
+
+/// Running sum of the frames passed to [`Painter::accumulate`].
+pub(crate) struct Accumulation {
+    /// Linear premultiplied sum of every frame.
+    sum: Buffer2d<Rgba>,
+    frames: usize,
+}
+
+impl Painter {
+    /// Adds a painted frame to the running average returned by [`Painter::resolve`]. A frame of a
+    /// different size than the previous ones restarts the average.
+    pub fn accumulate(&mut self, frame: &Buffer2d<u32>) {
+        let size = frame.size();
+        let accumulation = match &mut self.accumulation {
+            Some(accumulation) if accumulation.sum.size() == size => accumulation,
+            accumulation => accumulation.insert(Accumulation {
+                sum: Buffer2d::fill(size, Rgba::TRANSPARENT),
+                frames: 0,
+            }),
+        };
+
+        let [width, height] = size;
+        for y in 0..height {
+            for x in 0..width {
+                let sum = accumulation.sum.read([x, y]) + unpack_pixel(frame.read([x, y]));
+                accumulation.sum.write(x, y, sum);
+            }
+        }
+        accumulation.frames += 1;
+    }
+
+    /// The average of the frames accumulated since the last [`Painter::clear_accumulation`],
+    /// taken in linear space. `None` if nothing was accumulated.
+    pub fn resolve(&self) -> Option<Buffer2d<u32>> {
+        let accumulation = self.accumulation.as_ref()?;
+        let scale = 1.0 / accumulation.frames as f32;
+
+        let size = accumulation.sum.size();
+        let mut output = Buffer2d::fill(size, 0);
+        let [width, height] = size;
+        for y in 0..height {
+            for x in 0..width {
+                output.write(x, y, pack_pixel(accumulation.sum.read([x, y]) * scale));
+            }
+        }
+        Some(output)
+    }
+
+    /// Discards the frames accumulated so far.
+    pub fn clear_accumulation(&mut self) {
+        self.accumulation = None;
+    }
+}
@@ -0,0 +1,168 @@
+//! Conversions between the pixel formats used by the crate.
+//!
+//! Output buffers hold sRGBA bytes with unmultiplied alpha, packed little-endian into `u32`s
+//! (`[r, g, b, a]` in memory), which is what [`unpack_pixel`] and [`pack_pixel`] read and write.
+//! Colors in flight through the pipeline are linear premultiplied [`Rgba`].
+
+use egui::ecolor::{gamma_from_linear, linear_from_gamma};
+use egui::{Color32, Rgba};
+
+/// Decodes a pixel of an output buffer (sRGBA bytes, unmultiplied alpha) into linear premultiplied color.
+pub fn unpack_pixel(pixel: u32) -> Rgba {
+    let [r, g, b, a] = pixel.to_le_bytes();
+    Color32::from_rgba_unmultiplied(r, g, b, a).into()
+}
+
+/// Encodes linear premultiplied color as a pixel of an output buffer.
+pub fn pack_pixel(color: Rgba) -> u32 {
+    u32::from_le_bytes(color.to_srgba_unmultiplied())
+}
+
+/// Like [`pack_pixel`], but dithers the quantization to 8 bits with a 4x4 ordered pattern
+/// depending on the pixel position `[x, y]`. Smooth gradients, most visibly dark ones where sRGB
+/// steps are largest, then show fine noise instead of bands.
+pub fn pack_pixel_dithered(color: Rgba, [x, y]: [usize; 2]) -> u32 {
+    const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+    let offset = (BAYER[y % 4][x % 4] as f32 + 0.5) / 16.0 - 0.5;
+    let quantize = |value: f32| (value * 255.0 + offset).round().clamp(0.0, 255.0) as u8;
+
+    let color = unpremultiply(color);
+    let [r, g, b] = [color.r(), color.g(), color.b()].map(|c| quantize(gamma_from_linear(c)));
+    u32::from_le_bytes([r, g, b, quantize(color.a())])
+}
+
+/// Turns linear color with straight alpha into premultiplied color.
+pub fn premultiply(color: Rgba) -> Rgba {
+    let [r, g, b, a] = [color.r(), color.g(), color.b(), color.a()];
+    Rgba::from_rgba_premultiplied(r * a, g * a, b * a, a)
+}
+
+/// Turns linear premultiplied color into color with straight alpha. Fully transparent colors
+/// become transparent black.
+pub fn unpremultiply(color: Rgba) -> Rgba {
+    let [r, g, b, a] = [color.r(), color.g(), color.b(), color.a()];
+    if a <= 0.0 {
+        Rgba::TRANSPARENT
+    } else {
+        Rgba::from_rgba_premultiplied(r / a, g / a, b / a, a)
+    }
+}
+
+/// Encodes linear premultiplied color as sRGB premultiplied in gamma space, like the bytes of a
+/// [`Color32`] but without quantizing them.
+pub(crate) fn encode_srgb(color: Rgba) -> Rgba {
+    let color = unpremultiply(color);
+    let a = color.a();
+    let [r, g, b] = [color.r(), color.g(), color.b()].map(|c| gamma_from_linear(c) * a);
+    Rgba::from_rgba_premultiplied(r, g, b, a)
+}
+
+/// The inverse of [`encode_srgb`].
+pub(crate) fn decode_srgb(color: Rgba) -> Rgba {
+    let a = color.a();
+    if a <= 0.0 {
+        return Rgba::TRANSPARENT;
+    }
+    let [r, g, b] = [color.r(), color.g(), color.b()].map(|c| linear_from_gamma(c / a) * a);
+    Rgba::from_rgba_premultiplied(r, g, b, a)
+}
+
+/// Converts a pixel of an output buffer into premultiplied sRGBA bytes, i.e. the layout of
+/// [`Color32`] and [`PackedTexture`](crate::PackedTexture).
+pub fn premultiply_u32(pixel: u32) -> u32 {
+    let [r, g, b, a] = pixel.to_le_bytes();
+    u32::from_le_bytes(Color32::from_rgba_unmultiplied(r, g, b, a).to_array())
+}
+
+/// Converts premultiplied sRGBA bytes into a pixel of an output buffer. The inverse of
+/// [`premultiply_u32`], up to rounding.
+pub fn unpremultiply_u32(pixel: u32) -> u32 {
+    let [r, g, b, a] = pixel.to_le_bytes();
+    u32::from_le_bytes(Color32::from_rgba_premultiplied(r, g, b, a).to_srgba_unmultiplied())
+}
+
+/// How a layer or fragment is combined with what is below it, see [`composite`](crate::composite)
+/// and [`Painter::set_blend_op`](crate::Painter::set_blend_op).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BlendOp {
+    /// Source-over alpha compositing, as used for rendering.
+    #[default]
+    Over,
+    /// Adds the layer to what is below, e.g. for glows and highlights.
+    Add,
+    /// Multiplies what is below by the layer, where the layer is opaque.
+    Multiply,
+    /// Replaces what is below, alpha included.
+    Replace,
+}
+
+impl BlendOp {
+    /// Combines linear premultiplied `src` onto `dst`.
+    pub fn blend(self, dst: Rgba, src: Rgba) -> Rgba {
+        match self {
+            BlendOp::Over => over(dst, src),
+            BlendOp::Add => dst + src,
+            BlendOp::Multiply => {
+                // Where the layer is transparent, `dst` shows through unchanged
+                let mut color = dst * (src + Rgba::WHITE * (1.0 - src.a()));
+                color[3] = dst.a();
+                color
+            }
+            BlendOp::Replace => src,
+        }
+    }
+}
+
+/// Premultiplied source-over compositing of `fragment` onto `screen`.
+pub(crate) fn over(screen: Rgba, fragment: Rgba) -> Rgba {
+    let mut color = fragment + screen * (1.0 - fragment.a());
+    color[3] = screen.a() + fragment.a() * (1.0 - screen.a());
+    color
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transparent_colors_unpremultiply_to_transparent_black() {
+        let additive = Rgba::from_rgba_premultiplied(0.3, 0.2, 0.1, 0.0);
+        assert_eq!(unpremultiply(additive), Rgba::TRANSPARENT);
+        assert_eq!(unpremultiply(Rgba::TRANSPARENT), Rgba::TRANSPARENT);
+        assert_eq!(premultiply(additive), Rgba::TRANSPARENT);
+    }
+
+    #[test]
+    fn opaque_colors_are_unchanged() {
+        let color = Rgba::from_rgba_premultiplied(0.3, 0.2, 0.1, 1.0);
+        assert_eq!(premultiply(color), color);
+        assert_eq!(unpremultiply(color), color);
+
+        let pixel = u32::from_le_bytes([200, 100, 50, 255]);
+        assert_eq!(premultiply_u32(pixel), pixel);
+        assert_eq!(unpremultiply_u32(pixel), pixel);
+    }
+
+    #[test]
+    fn premultiplying_round_trips() {
+        let color = Rgba::from_rgba_premultiplied(0.8, 0.4, 0.2, 0.5);
+        let straight = unpremultiply(color);
+        assert_eq!(straight, Rgba::from_rgba_premultiplied(1.6, 0.8, 0.4, 0.5));
+        assert_eq!(premultiply(straight), color);
+
+        assert_eq!(premultiply_u32(0), 0);
+        assert_eq!(unpremultiply_u32(0), 0);
+        for a in [1, 64, 128, 254] {
+            let pixel = u32::from_le_bytes([200, 100, 50, a]);
+            let premultiplied = premultiply_u32(pixel).to_le_bytes();
+            assert_eq!(premultiplied[3], a);
+            if a >= 64 {
+                let round_trip = unpremultiply_u32(premultiply_u32(pixel)).to_le_bytes();
+                let expected = pixel.to_le_bytes();
+                for (c, e) in round_trip.into_iter().zip(expected) {
+                    assert!(c.abs_diff(e) <= 4, "{round_trip:?} instead of {expected:?}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,231 @@
+//! Debug visualisations of the output.
+
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+use egui::{epaint, Color32, Pos2, Rgba};
+use euc::{Buffer2d, CullMode, Pipeline, Target, Texture, TriangleList};
+
+use crate::color::over;
+use crate::{egui_coord_to_ndc, pack_pixel, unpack_pixel, Affine2, EguiVertexData};
+
+/// Extra information a [`Painter`](crate::Painter) draws over its output, see
+/// [`Painter::set_debug_mode`](crate::Painter::set_debug_mode).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DebugMode {
+    /// Draw the output only.
+    #[default]
+    None,
+    /// Outline the scissor rectangle of every drawn primitive with a 1px magenta line.
+    ClipRects,
+    /// Draw the edges of every drawn triangle with a 1px cyan line, to show how shapes were
+    /// tessellated.
+    Wireframe,
+}
+
+/// Outline color used by [`DebugMode::ClipRects`], as an output pixel.
+pub(crate) const CLIP_RECT_COLOR: u32 = u32::from_le_bytes([255, 0, 255, 255]);
+
+/// Edge color used by [`DebugMode::Wireframe`].
+pub(crate) const WIREFRAME_COLOR: Color32 = Color32::from_rgb(0, 255, 255);
+
+/// Pixel of a UV buffer which no mesh covered, see
+/// [`Painter::sampled_uv_at`](crate::Painter::sampled_uv_at).
+pub(crate) const NO_UV: u32 = u32::MAX;
+
+/// Steps per UV unit of a UV buffer pixel. One step short of `u16::MAX`, so no UV is quantized to
+/// [`NO_UV`].
+const UV_STEPS: f32 = 65534.0;
+
+/// Packs `uv`, clamped to `[0, 1]`, into a UV buffer pixel: u in the low and v in the high 16 bits.
+fn quantize_uv(uv: Pos2) -> u32 {
+    let quantize = |c: f32| (c.clamp(0.0, 1.0) * UV_STEPS).round() as u32;
+    quantize(uv.x) | quantize(uv.y) << 16
+}
+
+/// The UV packed into a UV buffer pixel by [`quantize_uv`], if any.
+pub(crate) fn dequantize_uv(pixel: u32) -> Option<Pos2> {
+    (pixel != NO_UV).then(|| {
+        let dequantize = |c: u32| (c & 0xffff) as f32 / UV_STEPS;
+        Pos2::new(dequantize(pixel), dequantize(pixel >> 16))
+    })
+}
+
+/// Rasterizes egui meshes like [`EguiMeshEucPipeline`](crate::EguiMeshEucPipeline), but writes the
+/// quantized UV of each fragment instead of its color.
+pub(crate) struct UvPipeline<'r> {
+    pub(crate) vertices: &'r [epaint::Vertex],
+    pub(crate) screen_size_points: egui::Vec2,
+    pub(crate) transform: Affine2,
+}
+
+impl<'r> Pipeline<'r> for UvPipeline<'r> {
+    type Vertex = u32;
+    type VertexData = EguiVertexData;
+    type Primitives = TriangleList;
+    type Pixel = u32;
+    type Fragment = u32;
+
+    #[inline(always)]
+    fn vertex(&self, idx: &Self::Vertex) -> ([f32; 4], Self::VertexData) {
+        let vertex = self.vertices[*idx as usize];
+        let [x, y] = egui_coord_to_ndc(self.transform.apply(vertex.pos), self.screen_size_points);
+        ([x, y, 0.0, 1.0], vertex.into())
+    }
+
+    #[inline(always)]
+    fn fragment(&self, data: Self::VertexData) -> Self::Fragment {
+        quantize_uv(data.uv)
+    }
+
+    fn blend(&self, _screen: Self::Pixel, fragment: Self::Fragment) -> Self::Pixel {
+        fragment
+    }
+
+    fn rasterizer_config(&self) -> CullMode {
+        CullMode::None
+    }
+}
+
+/// Draws a 1px outline just inside of `[x, y, width, height]`.
+pub(crate) fn outline_rect(
+    buffer: &mut Buffer2d<u32>,
+    [x, y, width, height]: [usize; 4],
+    color: u32,
+) {
+    if width == 0 || height == 0 {
+        return;
+    }
+    let (right, bottom) = (x + width - 1, y + height - 1);
+    for x in x..=right {
+        buffer.write(x, y, color);
+        buffer.write(x, bottom, color);
+    }
+    for y in y..=bottom {
+        buffer.write(x, y, color);
+        buffer.write(right, y, color);
+    }
+}
+
+/// Composites `buffer` over a checkerboard of light gray and white squares `cell_size` pixels wide,
+/// like image editors show transparency, for previews of translucent GUIs. The result is opaque.
+pub fn composite_over_checkerboard(buffer: &Buffer2d<u32>, cell_size: usize) -> Buffer2d<u32> {
+    let [width, height] = buffer.size();
+    let cell_size = cell_size.max(1);
+    let (light, dark) = (Rgba::WHITE, Rgba::from(Color32::from_gray(204)));
+
+    let mut out = Buffer2d::fill([width, height], 0);
+    for y in 0..height {
+        for x in 0..width {
+            let backdrop = if (x / cell_size + y / cell_size) % 2 == 0 {
+                light
+            } else {
+                dark
+            };
+            let color = over(backdrop, unpack_pixel(buffer.read([x, y])));
+            out.write(x, y, pack_pixel(color));
+        }
+    }
+    out
+}
+
+/// Prints a preview of `buffer` to stdout with ANSI truecolor blocks, at most `max_width`
+/// characters wide, e.g. to look at a frame in CI logs. See [`write_buffer_preview`].
+#[cfg(feature = "std")]
+pub fn debug_print_buffer(buffer: &Buffer2d<u32>, max_width: usize) {
+    // Failing to print debug output isn't worth reporting
+    let _ = write_buffer_preview(&mut io::stdout().lock(), buffer, max_width);
+}
+
+/// Writes a preview of `buffer` to `out` with ANSI truecolor blocks, at most `max_width`
+/// characters wide. Each character shows two box filtered cells stacked vertically, composited
+/// over black.
+#[cfg(feature = "std")]
+pub fn write_buffer_preview(
+    out: &mut impl Write,
+    buffer: &Buffer2d<u32>,
+    max_width: usize,
+) -> io::Result<()> {
+    let [width, height] = buffer.size();
+    // Side of the square of pixels averaged into each cell
+    let cell = width.div_ceil(max_width.max(1)).max(1);
+
+    let average = |cell_x: usize, cell_y: usize| {
+        let mut sum = Rgba::TRANSPARENT;
+        let mut count = 0;
+        for y in cell_y * cell..((cell_y + 1) * cell).min(height) {
+            for x in cell_x * cell..((cell_x + 1) * cell).min(width) {
+                sum = sum + unpack_pixel(buffer.read([x, y]));
+                count += 1;
+            }
+        }
+        // Premultiplied color is already composited over black
+        let sum = sum * (1.0 / count.max(1) as f32);
+        Color32::from(Rgba::from_rgb(sum.r(), sum.g(), sum.b()))
+    };
+
+    let (columns, rows) = (width.div_ceil(cell), height.div_ceil(cell));
+    for row in (0..rows).step_by(2) {
+        for column in 0..columns {
+            let top = average(column, row);
+            let bottom = if row + 1 < rows {
+                average(column, row + 1)
+            } else {
+                Color32::BLACK
+            };
+            write!(
+                out,
+                "\x1b[38;2;{};{};{}m\x1b[48;2;{};{};{}m\u{2580}",
+                top.r(),
+                top.g(),
+                top.b(),
+                bottom.r(),
+                bottom.g(),
+                bottom.b()
+            )?;
+        }
+        writeln!(out, "\x1b[0m")?;
+    }
+    Ok(())
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use super::*;
+
+    const RED: u32 = u32::from_le_bytes([255, 0, 0, 255]);
+    const BLUE: u32 = u32::from_le_bytes([0, 0, 255, 255]);
+
+    fn preview(buffer: &Buffer2d<u32>, max_width: usize) -> String {
+        let mut out = Vec::new();
+        write_buffer_preview(&mut out, buffer, max_width).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn preview_stacks_two_rows_per_line() {
+        // Red over blue
+        let mut buffer = Buffer2d::fill([2, 2], BLUE);
+        buffer.write(0, 0, RED);
+        buffer.write(1, 0, RED);
+
+        let cell = "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m\u{2580}";
+        assert_eq!(preview(&buffer, 2), format!("{cell}{cell}\x1b[0m\n"));
+    }
+
+    #[test]
+    fn preview_is_downsampled_to_max_width() {
+        // Red on the left and blue on the right, averaged into one cell each
+        let mut buffer = Buffer2d::fill([4, 2], BLUE);
+        for y in 0..2 {
+            buffer.write(0, y, RED);
+            buffer.write(1, y, RED);
+        }
+
+        assert_eq!(
+            preview(&buffer, 2),
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;0;0m\u{2580}\
+             \x1b[38;2;0;0;255m\x1b[48;2;0;0;0m\u{2580}\x1b[0m\n"
+        );
+    }
+}
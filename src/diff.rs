@@ -0,0 +1,384 @@
+//! Primitive-level diffing, so that mostly static frames only re-rasterize what changed.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use egui::{epaint, ClippedPrimitive, TextureId, TexturesDelta};
+use euc::{Buffer2d, Target, Texture};
+
+use crate::{remap_texture_id, Affine2, Painter, TextureIdRemap};
+
+/// The output of previous calls to [`Painter::paint_buffered`], and what it was drawn from.
+pub(crate) struct RetainedFrame {
+    /// Ring of output buffers, all of the same size.
+    buffers: Vec<Buffer2d<u32>>,
+    /// Index of the most recently rendered buffer.
+    front: usize,
+    pixels_per_point: f32,
+    primitives: Vec<PrimitiveSummary>,
+}
+
+impl RetainedFrame {
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.primitives.shrink_to_fit();
+    }
+}
+
+#[derive(Clone, Copy)]
+struct PrimitiveSummary {
+    hash: u64,
+    /// Pixels the primitive can touch, as `[x, y, width, height]`.
+    bounds: [usize; 4],
+    /// Version of the texture sampled by the primitive, `None` if it samples none or the texture
+    /// isn't allocated. Uploads, region updates and render to texture all change it.
+    texture_version: Option<u64>,
+}
+
+impl PrimitiveSummary {
+    fn new(
+        item: &ClippedPrimitive,
+        texture_versions: &HashMap<TextureId, u64>,
+        texture_id_remap: Option<&TextureIdRemap>,
+        pixel_scale: egui::Vec2,
+        to_target: Affine2,
+        screen_size: [usize; 2],
+    ) -> Self {
+        let texture_version = match &item.primitive {
+            epaint::Primitive::Mesh(mesh) => {
+                let texture_id = remap_texture_id(texture_id_remap, mesh.texture_id);
+                texture_versions.get(&texture_id).copied()
+            }
+            epaint::Primitive::Callback(_) => None,
+        };
+
+        Self {
+            hash: hash_primitive(item),
+            bounds: primitive_bounds(item, pixel_scale, to_target, screen_size),
+            texture_version,
+        }
+    }
+
+    fn needs_redraw(old: Option<&Self>, new: Option<&Self>) -> bool {
+        match (old, new) {
+            (Some(old), Some(new)) => {
+                old.hash != new.hash
+                    || old.bounds != new.bounds
+                    || old.texture_version != new.texture_version
+            }
+            (None, None) => false,
+            _ => true,
+        }
+    }
+}
+
+impl Painter {
+    /// Like [`Painter::paint_and_update_textures`], but keeps the result and, on the next call, only
+    /// re-rasterizes the region covered by primitives which differ from the previous frame.
+    ///
+    /// Primitives are compared by position in the list, so inserting a primitive damages everything
+    /// drawn after it. Unchanged primitives overlapping the damaged region are redrawn too, to keep
+    /// blending order intact.
+    pub fn paint_diff(
+        &mut self,
+        textures_delta: &TexturesDelta,
+        clipped_primitives: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        screen_size: [usize; 2],
+    ) -> &Buffer2d<u32> {
+        let index = self.paint_buffered(
+            textures_delta,
+            clipped_primitives,
+            pixels_per_point,
+            screen_size,
+        );
+        self.buffer(index)
+    }
+
+    /// Sets how many output buffers [`Painter::paint_buffered`] cycles through, e.g. 2 for double
+    /// buffering. Defaults to 1.
+    pub fn set_buffer_count(&mut self, count: usize) {
+        self.buffer_count = count.max(1);
+    }
+
+    /// Paints the next buffer of the ring set up by [`Painter::set_buffer_count`], and returns its
+    /// index for [`Painter::buffer`]. The buffer presented before it is left untouched, so it can
+    /// still be scanned out while this one is drawn.
+    ///
+    /// As with [`Painter::paint_diff`], only the regions damaged since the previous frame are
+    /// re-rasterized. The rest is carried over from the previously rendered buffer.
+    pub fn paint_buffered(
+        &mut self,
+        textures_delta: &TexturesDelta,
+        clipped_primitives: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        screen_size: [usize; 2],
+    ) -> usize {
+        self.paint_damaged(
+            textures_delta,
+            clipped_primitives,
+            pixels_per_point,
+            screen_size,
+        )
+        .0
+    }
+
+    /// Like [`Painter::paint_diff`], additionally returning every tile of `tile_size` pixels which
+    /// changed since the previous frame, so only those need to be sent to a display over a slow
+    /// bus. Tiles at the right and bottom edges may be cut short by the buffer.
+    pub fn paint_tiled_damage(
+        &mut self,
+        textures_delta: &TexturesDelta,
+        clipped_primitives: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        screen_size: [usize; 2],
+        tile_size: [usize; 2],
+    ) -> (&Buffer2d<u32>, Vec<TileId>) {
+        let (index, damage) = self.paint_damaged(
+            textures_delta,
+            clipped_primitives,
+            pixels_per_point,
+            screen_size,
+        );
+        (self.buffer(index), damaged_tiles(&damage, tile_size))
+    }
+
+    /// Implements [`Painter::paint_buffered`], additionally returning the rectangles whose pixels
+    /// may have changed: the old and new bounds of every primitive which changed.
+    fn paint_damaged(
+        &mut self,
+        textures_delta: &TexturesDelta,
+        clipped_primitives: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        screen_size: [usize; 2],
+    ) -> (usize, Vec<[usize; 4]>) {
+        self.begin_frame(textures_delta);
+
+        let primitives: Vec<PrimitiveSummary> = clipped_primitives
+            .iter()
+            .map(|item| {
+                PrimitiveSummary::new(
+                    item,
+                    &self.texture_versions,
+                    self.texture_id_remap.as_deref(),
+                    self.pixel_scale(pixels_per_point),
+                    self.to_target(screen_size, self.pixel_scale(pixels_per_point)),
+                    screen_size,
+                )
+            })
+            .collect();
+
+        let count = self.buffer_count;
+        let retained = self.retained.take().filter(|retained| {
+            retained.buffers.len() == count
+                && retained.buffers[0].size() == screen_size
+                && retained.pixels_per_point == pixels_per_point
+        });
+
+        let [width, height] = screen_size;
+        let full_screen = [0, 0, width, height];
+
+        let (mut buffers, back, damage) = match retained {
+            Some(retained) => {
+                let back = (retained.front + 1) % count;
+                let mut buffers = retained.buffers;
+                if back != retained.front {
+                    let (front, back) = pair_mut(&mut buffers, retained.front, back);
+                    copy_buffer(front, back);
+                }
+                let damage = damaged_rects(&retained.primitives, &primitives);
+                (buffers, back, damage)
+            }
+            None => {
+                let buffers = (0..count).map(|_| Buffer2d::fill(screen_size, 0)).collect();
+                (buffers, 0, vec![full_screen])
+            }
+        };
+
+        // Everything under the bounding box is redrawn, so blending order stays intact, but only
+        // the damaged rectangles within it end up with different pixels
+        let region = damage
+            .iter()
+            .fold(None, |region, &rect| union(region, rect));
+        if let Some(damage) = region {
+            clear_region(&mut buffers[back], damage);
+            self.render_into(
+                &mut buffers[back],
+                clipped_primitives,
+                pixels_per_point,
+                damage,
+            );
+        }
+
+        self.retained = Some(RetainedFrame {
+            buffers,
+            front: back,
+            pixels_per_point,
+            primitives,
+        });
+
+        self.end_frame();
+
+        (back, damage)
+    }
+
+    /// One of the buffers painted by [`Painter::paint_buffered`].
+    ///
+    /// # Panics
+    /// If nothing has been painted into the buffer ring yet, or `index` is out of range.
+    pub fn buffer(&self, index: usize) -> &Buffer2d<u32> {
+        &self
+            .retained
+            .as_ref()
+            .expect("No buffered frame has been painted")
+            .buffers[index]
+    }
+}
+
+/// A tile of the output, by column and row, see [`Painter::paint_tiled_damage`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TileId {
+    pub column: usize,
+    pub row: usize,
+}
+
+impl TileId {
+    /// The pixels of the tile within a buffer of `screen_size`, as `[x, y, width, height]`.
+    pub fn rect(
+        self,
+        [tile_width, tile_height]: [usize; 2],
+        [width, height]: [usize; 2],
+    ) -> [usize; 4] {
+        let x = (self.column * tile_width).min(width);
+        let y = (self.row * tile_height).min(height);
+        [x, y, tile_width.min(width - x), tile_height.min(height - y)]
+    }
+}
+
+/// Every tile overlapping any of the `[x, y, width, height]` rectangles of `damage`, row by row.
+fn damaged_tiles(damage: &[[usize; 4]], tile_size: [usize; 2]) -> Vec<TileId> {
+    let [tile_width, tile_height] = tile_size.map(|side| side.max(1));
+    let mut tiles: Vec<TileId> = damage
+        .iter()
+        .filter(|&&[_, _, width, height]| width > 0 && height > 0)
+        .flat_map(|&[x, y, width, height]| {
+            let columns = x / tile_width..=(x + width - 1) / tile_width;
+            (y / tile_height..=(y + height - 1) / tile_height)
+                .flat_map(move |row| columns.clone().map(move |column| TileId { column, row }))
+        })
+        .collect();
+    tiles.sort_unstable_by_key(|tile| (tile.row, tile.column));
+    tiles.dedup();
+    tiles
+}
+
+/// Borrows `buffers[a]` immutably and `buffers[b]` mutably, where `a != b`.
+fn pair_mut<T>(buffers: &mut [T], a: usize, b: usize) -> (&T, &mut T) {
+    if a < b {
+        let (low, high) = buffers.split_at_mut(b);
+        (&low[a], &mut high[0])
+    } else {
+        let (low, high) = buffers.split_at_mut(a);
+        (&high[0], &mut low[b])
+    }
+}
+
+fn copy_buffer(src: &Buffer2d<u32>, dst: &mut Buffer2d<u32>) {
+    let [width, height] = src.size();
+    for y in 0..height {
+        for x in 0..width {
+            dst.write(x, y, src.read([x, y]));
+        }
+    }
+}
+
+/// Hashes everything about a primitive which affects how it is drawn, except texture contents.
+fn hash_primitive(item: &ClippedPrimitive) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    for coord in [item.clip_rect.min, item.clip_rect.max] {
+        coord.x.to_bits().hash(&mut hasher);
+        coord.y.to_bits().hash(&mut hasher);
+    }
+
+    if let epaint::Primitive::Mesh(mesh) = &item.primitive {
+        mesh.texture_id.hash(&mut hasher);
+        mesh.indices.hash(&mut hasher);
+        for vertex in &mesh.vertices {
+            vertex.pos.x.to_bits().hash(&mut hasher);
+            vertex.pos.y.to_bits().hash(&mut hasher);
+            vertex.uv.x.to_bits().hash(&mut hasher);
+            vertex.uv.y.to_bits().hash(&mut hasher);
+            vertex.color.hash(&mut hasher);
+        }
+    }
+
+    hasher.finish()
+}
+
+/// Pixels a primitive can touch: its mesh bounds within its clip rect, rounded outwards.
+pub(crate) fn primitive_bounds(
+    item: &ClippedPrimitive,
+    pixel_scale: egui::Vec2,
+    to_target: Affine2,
+    [width_px, height_px]: [usize; 2],
+) -> [usize; 4] {
+    let rect = match &item.primitive {
+        epaint::Primitive::Mesh(mesh) => {
+            to_target.apply_rect(mesh.calc_bounds().intersect(item.clip_rect))
+        }
+        epaint::Primitive::Callback(_) => return [0, 0, 0, 0],
+    };
+
+    let min_x = ((rect.min.x * pixel_scale.x).floor() as i32).clamp(0, width_px as i32);
+    let min_y = ((rect.min.y * pixel_scale.y).floor() as i32).clamp(0, height_px as i32);
+    let max_x = ((rect.max.x * pixel_scale.x).ceil() as i32).clamp(min_x, width_px as i32);
+    let max_y = ((rect.max.y * pixel_scale.y).ceil() as i32).clamp(min_y, height_px as i32);
+
+    [
+        min_x as usize,
+        min_y as usize,
+        (max_x - min_x) as usize,
+        (max_y - min_y) as usize,
+    ]
+}
+
+/// The bounds of every primitive which was added, removed or changed, before and after the change.
+fn damaged_rects(old: &[PrimitiveSummary], new: &[PrimitiveSummary]) -> Vec<[usize; 4]> {
+    let mut damage = Vec::new();
+
+    for i in 0..old.len().max(new.len()) {
+        let (old, new) = (old.get(i), new.get(i));
+        if !PrimitiveSummary::needs_redraw(old, new) {
+            continue;
+        }
+        let bounds = old.into_iter().chain(new).map(|summary| summary.bounds);
+        damage.extend(bounds.filter(|&[_, _, width, height]| width > 0 && height > 0));
+    }
+
+    damage
+}
+
+pub(crate) fn union(rect: Option<[usize; 4]>, other: [usize; 4]) -> Option<[usize; 4]> {
+    let [x, y, width, height] = other;
+    if width == 0 || height == 0 {
+        return rect;
+    }
+    let Some([rx, ry, rw, rh]) = rect else {
+        return Some(other);
+    };
+
+    let min_x = rx.min(x);
+    let min_y = ry.min(y);
+    let max_x = (rx + rw).max(x + width);
+    let max_y = (ry + rh).max(y + height);
+    Some([min_x, min_y, max_x - min_x, max_y - min_y])
+}
+
+pub(crate) fn clear_region(buffer: &mut Buffer2d<u32>, [x, y, width, height]: [usize; 4]) {
+    for y in y..y + height {
+        for x in x..x + width {
+            buffer.write(x, y, 0);
+        }
+    }
+}
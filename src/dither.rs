@@ -0,0 +1,74 @@
+//! Dithered output, see [`Painter::set_dither`](crate::Painter::set_dither).
+
+use egui::Rgba;
+use euc::{CullMode, Pipeline, Target, Texture};
+
+use crate::{pack_pixel_dithered, unpack_pixel, EguiMeshEucPipeline, UvSampler};
+
+/// Wrapper of a euc::Target of output pixels taking linear premultiplied color, which it encodes
+/// with [`pack_pixel_dithered`] at the position of each write.
+pub(crate) struct Dithered<T> {
+    pub(crate) inner: T,
+}
+
+impl<T> Texture<2> for Dithered<T>
+where
+    T: Texture<2, Index = usize, Texel = u32>,
+{
+    type Index = usize;
+    type Texel = Rgba;
+
+    fn size(&self) -> [Self::Index; 2] {
+        self.inner.size()
+    }
+
+    fn read(&self, index: [Self::Index; 2]) -> Self::Texel {
+        unpack_pixel(self.inner.read(index))
+    }
+}
+
+impl<T: Target<Texel = u32>> Target for Dithered<T> {
+    unsafe fn read_exclusive_unchecked(&self, x: usize, y: usize) -> Self::Texel {
+        unpack_pixel(unsafe { self.inner.read_exclusive_unchecked(x, y) })
+    }
+
+    unsafe fn write_exclusive_unchecked(&self, x: usize, y: usize, texel: Self::Texel) {
+        unsafe {
+            self.inner
+                .write_exclusive_unchecked(x, y, pack_pixel_dithered(texel, [x, y]));
+        }
+    }
+}
+
+/// [`EguiMeshEucPipeline`] blending in linear color, for drawing into a [`Dithered`] target which
+/// quantizes the result. Blending is done in floating point even with the `fixed-point` feature.
+pub(crate) struct DitheredPipeline<'r, S>(pub(crate) EguiMeshEucPipeline<'r, S>);
+
+impl<'r, S> Pipeline<'r> for DitheredPipeline<'r, S>
+where
+    S: UvSampler,
+{
+    type Vertex = u32;
+    type VertexData = <EguiMeshEucPipeline<'r, S> as Pipeline<'r>>::VertexData;
+    type Primitives = <EguiMeshEucPipeline<'r, S> as Pipeline<'r>>::Primitives;
+    type Pixel = Rgba;
+    type Fragment = Rgba;
+
+    #[inline(always)]
+    fn vertex(&self, idx: &Self::Vertex) -> ([f32; 4], Self::VertexData) {
+        self.0.vertex(idx)
+    }
+
+    #[inline(always)]
+    fn fragment(&self, data: Self::VertexData) -> Self::Fragment {
+        self.0.fragment(data)
+    }
+
+    fn blend(&self, screen: Self::Pixel, fragment: Self::Fragment) -> Self::Pixel {
+        self.0.blend_op.blend(screen, fragment)
+    }
+
+    fn rasterizer_config(&self) -> CullMode {
+        self.0.rasterizer_config()
+    }
+}
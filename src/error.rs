@@ -0,0 +1,57 @@
+//! Problems with the input of a [`Painter`](crate::Painter) which it recovers from.
+
+use std::fmt;
+
+use egui::TextureId;
+
+use crate::MeshError;
+
+/// Input which a [`Painter`](crate::Painter) skipped instead of panicking, see
+/// [`Painter::last_errors`](crate::Painter::last_errors).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PainterError {
+    /// A delta replaced all of texture `id` with an image without pixels. The delta was skipped,
+    /// leaving the texture as it was, or unallocated if it had never been uploaded.
+    EmptyImage { id: TextureId },
+    /// Something referenced texture `id` while it wasn't allocated, e.g. a mesh drawn before the
+    /// [`TexturesDelta`](egui::TexturesDelta) setting it was passed to the painter, or after the one
+    /// freeing it. Meshes sampling it are skipped, and are reported once per frame.
+    MissingTexture { id: TextureId },
+    /// The mesh at index `primitive` of the primitives passed to a paint call failed
+    /// [`validate_mesh`](crate::validate_mesh) with `error`, and was skipped.
+    InvalidMesh { primitive: usize, error: MeshError },
+}
+
+impl fmt::Display for PainterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PainterError::EmptyImage { id } => {
+                write!(f, "texture {id:?} was replaced with an empty image")
+            }
+            PainterError::MissingTexture {
+                id: TextureId::Managed(id),
+            } => write!(
+                f,
+                "absent managed texture {id} was referenced: was the TexturesDelta which set it passed to the painter?"
+            ),
+            PainterError::MissingTexture {
+                id: TextureId::User(id),
+            } => write!(
+                f,
+                "absent user texture {id} was referenced: user textures must be added with Painter::register_user_texture"
+            ),
+            PainterError::InvalidMesh { primitive, error } => {
+                write!(f, "mesh of primitive {primitive} is invalid: {error}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PainterError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PainterError::InvalidMesh { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! Integer versions of the per-pixel color math, for targets without an FPU, enabled by the
+//! `fixed-point` feature.
+//!
+//! Colors are linear in Q12 fixed point (`4096` is `1.0`). Converting between sRGB bytes and
+//! linear color goes through lookup tables instead of `powf`, and blending uses integer math only.
+//! Nearest texture sampling turns UVs into texel indices with integer math too.
+
+use std::sync::OnceLock;
+
+use egui::{Rgba, TextureWrapMode};
+use euc::Texture;
+
+use crate::sampler::wrap_texel;
+use crate::UvSampler;
+
+const ONE: u32 = 1 << 12;
+
+/// Fractional bits of the UVs [`Nearest`] computes texel indices from.
+const UV_BITS: u32 = 16;
+
+/// `value * 2^bits` rounded toward negative infinity, from the bits of `value` rather than a float
+/// multiply. Subnormals and NaN are zero, and magnitudes of `2^62` and over saturate.
+fn to_fixed(value: f32, bits: u32) -> i64 {
+    let raw = value.to_bits();
+    let exponent = ((raw >> 23) & 0xff) as i32;
+    if exponent == 0 || (exponent == 0xff && raw & 0x7f_ffff != 0) {
+        return 0;
+    }
+    let mantissa = (raw & 0x7f_ffff | 0x80_0000) as i64;
+    let mantissa = if raw >> 31 == 0 { mantissa } else { -mantissa };
+    // The value is `mantissa * 2^(exponent - 150)`
+    let shift = exponent - 150 + bits as i32;
+    if shift < 0 {
+        // An arithmetic shift rounds toward negative infinity
+        mantissa >> (-shift).min(63)
+    } else if shift < 39 {
+        mantissa << shift
+    } else {
+        mantissa.signum() << 62
+    }
+}
+
+/// Linear Q12 value of each sRGB byte.
+fn decode_table() -> &'static [u16; 256] {
+    static TABLE: OnceLock<[u16; 256]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        std::array::from_fn(|i| {
+            let linear = egui::ecolor::linear_f32_from_gamma_u8(i as u8);
+            (linear * ONE as f32).round() as u16
+        })
+    })
+}
+
+/// sRGB byte of each linear Q12 value.
+fn encode_table() -> &'static [u8; ONE as usize + 1] {
+    static TABLE: OnceLock<[u8; ONE as usize + 1]> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        std::array::from_fn(|i| egui::ecolor::gamma_u8_from_linear_f32(i as f32 / ONE as f32))
+    })
+}
+
+/// Blends linear premultiplied `fragment` over the output pixel `screen`, like
+/// [`BlendOp::Over`](crate::BlendOp::Over) but with integer math once the fragment is converted.
+pub(crate) fn over(screen: u32, fragment: Rgba) -> u32 {
+    let decode = decode_table();
+    let fragment = [fragment.r(), fragment.g(), fragment.b(), fragment.a()]
+        .map(|c| to_fixed(c, 12).clamp(0, ONE as i64) as u32);
+
+    let [r, g, b, a] = screen.to_le_bytes();
+    let screen_a = (a as u32 * ONE + 127) / 255;
+    let screen = [r, g, b].map(|c| (decode[c as usize] as u32 * screen_a) >> 12);
+
+    let keep = ONE - fragment[3];
+    let out_a = fragment[3] + ((screen_a * keep) >> 12);
+    if out_a == 0 {
+        return 0;
+    }
+    let encode = encode_table();
+    let [r, g, b] = [0, 1, 2].map(|i| {
+        let premultiplied = fragment[i] + ((screen[i] * keep) >> 12);
+        encode[(premultiplied * ONE / out_a).min(ONE) as usize]
+    });
+    let a = ((out_a * 255 + ONE / 2) >> 12) as u8;
+    u32::from_le_bytes([r, g, b, a])
+}
+
+/// Nearest texel sampling like euc's `nearest()` samplers, but converting each UV to fixed point
+/// once and finding the texel and applying `wrap_mode` with integer math.
+pub(crate) struct Nearest<T> {
+    texture: T,
+    wrap_mode: TextureWrapMode,
+}
+
+impl<T> Nearest<T> {
+    pub(crate) fn new(texture: T, wrap_mode: TextureWrapMode) -> Self {
+        Self { texture, wrap_mode }
+    }
+}
+
+impl<T> UvSampler for Nearest<T>
+where
+    T: Texture<2, Index = usize, Texel = Rgba>,
+{
+    fn sample_uv(&self, [u, v]: [f32; 2]) -> Rgba {
+        let [width, height] = self.texture.size();
+        if width == 0 || height == 0 {
+            return Rgba::TRANSPARENT;
+        }
+        self.texture.read([
+            texel_index(u, width, self.wrap_mode),
+            texel_index(v, height, self.wrap_mode),
+        ])
+    }
+}
+
+/// Index of the texel of a texture `size` texels wide covering `uv`, i.e. `floor(uv * size)`
+/// wrapped by `wrap_mode`.
+fn texel_index(uv: f32, size: usize, wrap_mode: TextureWrapMode) -> usize {
+    // Keeps the product below from overflowing. UVs this far out lost their fraction anyway
+    let uv = to_fixed(uv, UV_BITS).clamp(-1 << 40, 1 << 40);
+    wrap_texel((uv * size as i64) >> UV_BITS, size, wrap_mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use euc::Buffer2d;
+
+    #[test]
+    fn to_fixed_matches_multiplying() {
+        let values = [
+            0.0, 1.0, -1.0, 0.5, 0.999, 1.5e-3, -2.75, -0.3, 1234.5678, 1e-9, -1e-9,
+        ];
+        for value in values {
+            let expected = (value as f64 * 4096.0).floor() as i64;
+            assert_eq!(to_fixed(value, 12), expected, "{value}");
+        }
+        assert_eq!(to_fixed(3e20, 12), 1 << 62);
+        assert_eq!(to_fixed(f32::NAN, 12), 0);
+        assert_eq!(to_fixed(f32::INFINITY, 12), 1 << 62);
+        assert_eq!(to_fixed(f32::NEG_INFINITY, 12), -1 << 62);
+    }
+
+    /// The integer index agrees with `floor(uv * size)` computed in floating point, except that a
+    /// UV less than one fixed point step, `2^-16`, after a texel boundary may land in the texel
+    /// before it.
+    #[test]
+    fn texel_index_matches_float_path() {
+        let step = 1.0 / (1 << UV_BITS) as f64;
+        let wrap_modes = [
+            TextureWrapMode::ClampToEdge,
+            TextureWrapMode::Repeat,
+            TextureWrapMode::MirroredRepeat,
+        ];
+        for size in [1, 3, 16, 255] {
+            for wrap_mode in wrap_modes {
+                for i in -3000..3000 {
+                    let uv = i as f32 * 0.001_37;
+                    let texels = uv as f64 * size as f64;
+                    let float = wrap_texel(texels.floor() as i64, size, wrap_mode);
+                    let fixed = texel_index(uv, size, wrap_mode);
+                    if fixed != float {
+                        let context = format!("{uv} in {size} texels, {wrap_mode:?}");
+                        assert!(texels - texels.floor() < step * size as f64, "{context}");
+                        let before = wrap_texel(texels.floor() as i64 - 1, size, wrap_mode);
+                        assert_eq!(fixed, before, "{context}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn wrap_modes_fold_indices_into_the_texture() {
+        let wrapped = |wrap_mode| {
+            (-5..9)
+                .map(|i| wrap_texel(i, 3, wrap_mode))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            wrapped(TextureWrapMode::ClampToEdge),
+            [0, 0, 0, 0, 0, 0, 1, 2, 2, 2, 2, 2, 2, 2]
+        );
+        assert_eq!(
+            wrapped(TextureWrapMode::Repeat),
+            [1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2, 0, 1, 2]
+        );
+        assert_eq!(
+            wrapped(TextureWrapMode::MirroredRepeat),
+            [1, 2, 2, 1, 0, 0, 1, 2, 2, 1, 0, 0, 1, 2]
+        );
+    }
+
+    #[test]
+    fn nearest_reads_the_covering_texel() {
+        let mut texture = Buffer2d::fill([4, 2], Rgba::TRANSPARENT);
+        euc::Target::write(&mut texture, 3, 1, Rgba::WHITE);
+        let sampler = Nearest::new(&texture, TextureWrapMode::ClampToEdge);
+        assert_eq!(sampler.sample_uv([0.8, 0.6]), Rgba::WHITE);
+        assert_eq!(sampler.sample_uv([2.0, 7.0]), Rgba::WHITE);
+        assert_eq!(sampler.sample_uv([0.7, 0.6]), Rgba::TRANSPARENT);
+        assert_eq!(sampler.sample_uv([0.8, 0.4]), Rgba::TRANSPARENT);
+    }
+}
@@ -0,0 +1,211 @@
+//! Helpers for feeding a host's input to an embedded [`SoftwareGui`](crate::SoftwareGui).
+
+use egui::{Event, Modifiers, PointerButton, Pos2, RawInput, Rect, Vec2};
+
+/// Builds a well-formed [`RawInput`] for rendering without a window, e.g. in tests or on a
+/// server: the screen rect, scale and time are filled in, along with any synthetic events. Pass
+/// the same `screen_size` to [`SoftwareGui::update`](crate::SoftwareGui::update).
+#[derive(Clone, Debug)]
+pub struct HeadlessInput {
+    screen_size: [usize; 2],
+    pixels_per_point: f32,
+    time: f64,
+    modifiers: Modifiers,
+    events: Vec<Event>,
+}
+
+impl HeadlessInput {
+    /// Input for a screen of `screen_size` pixels at `pixels_per_point`, at time zero and without
+    /// any events.
+    pub fn new(screen_size: [usize; 2], pixels_per_point: f32) -> Self {
+        Self {
+            screen_size,
+            pixels_per_point,
+            time: 0.0,
+            modifiers: Modifiers::default(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Sets the time in seconds, which drives animations. Keeping it fixed keeps frames
+    /// reproducible.
+    pub fn time(mut self, time: f64) -> Self {
+        self.time = time;
+        self
+    }
+
+    /// Sets the modifier keys held, for the input and the pointer events added after this.
+    pub fn modifiers(mut self, modifiers: Modifiers) -> Self {
+        self.modifiers = modifiers;
+        self
+    }
+
+    /// Adds `event`.
+    pub fn event(mut self, event: Event) -> Self {
+        self.events.push(event);
+        self
+    }
+
+    /// Adds a move of the pointer to `pos` (in points) followed by a press and release of the
+    /// primary button there.
+    pub fn click(self, pos: Pos2) -> Self {
+        let button = |pressed| Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: self.modifiers,
+        };
+        let (press, release) = (button(true), button(false));
+        self.event(Event::PointerMoved(pos))
+            .event(press)
+            .event(release)
+    }
+
+    /// Adds typed `text`.
+    pub fn text(self, text: impl Into<String>) -> Self {
+        self.event(Event::Text(text.into()))
+    }
+
+    pub fn build(&self) -> RawInput {
+        let [width, height] = self.screen_size;
+        let mut input = RawInput {
+            screen_rect: Some(Rect::from_min_size(
+                Pos2::ZERO,
+                Vec2::new(width as f32, height as f32) / self.pixels_per_point,
+            )),
+            time: Some(self.time),
+            modifiers: self.modifiers,
+            events: self.events.clone(),
+            ..Default::default()
+        };
+        input
+            .viewports
+            .entry(input.viewport_id)
+            .or_default()
+            .native_pixels_per_point = Some(self.pixels_per_point);
+        input
+    }
+}
+
+/// Moves all pointer positions in `input` so that `rect.min` becomes the origin, for a sub-GUI
+/// shown at `rect` within its host.
+pub fn remap_raw_input(input: &mut RawInput, rect: Rect) {
+    for event in &mut input.events {
+        if let Some(pos) = pointer_pos_mut(event) {
+            *pos -= rect.min.to_vec2();
+        }
+    }
+}
+
+/// Stops pointer events outside of `bounds` (in the sub-GUI's coordinates, i.e. after
+/// [`remap_raw_input`]) from reaching the sub-GUI, so hovers and clicks don't leak into it.
+///
+/// Moves outside become [`Event::PointerGone`], ending any hover. Button releases are kept
+/// wherever they happen, so a drag which leaves the sub-GUI doesn't get stuck.
+pub fn clip_pointer_events(input: &mut RawInput, bounds: Rect) {
+    input.events.retain_mut(|event| {
+        let Some(pos) = pointer_pos_mut(event) else {
+            return true;
+        };
+        if bounds.contains(*pos) {
+            return true;
+        }
+
+        match event {
+            Event::PointerMoved(_) => {
+                *event = Event::PointerGone;
+                true
+            }
+            Event::PointerButton { pressed, .. } => !*pressed,
+            _ => false,
+        }
+    });
+}
+
+fn pointer_pos_mut(event: &mut Event) -> Option<&mut egui::Pos2> {
+    match event {
+        Event::PointerMoved(pos) | Event::PointerButton { pos, .. } | Event::Touch { pos, .. } => {
+            Some(pos)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use egui::pos2;
+
+    fn button(pos: Pos2, pressed: bool) -> Event {
+        Event::PointerButton {
+            pos,
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    #[test]
+    fn headless_input_is_well_formed() {
+        let input = HeadlessInput::new([200, 100], 2.0)
+            .time(1.5)
+            .modifiers(Modifiers::SHIFT)
+            .click(pos2(10.0, 20.0))
+            .text("hi")
+            .build();
+
+        assert_eq!(
+            input.screen_rect,
+            Some(Rect::from_min_size(Pos2::ZERO, Vec2::new(100.0, 50.0)))
+        );
+        let viewport = &input.viewports[&input.viewport_id];
+        assert_eq!(viewport.native_pixels_per_point, Some(2.0));
+        assert_eq!(input.time, Some(1.5));
+        assert_eq!(input.modifiers, Modifiers::SHIFT);
+        let button = |pressed| Event::PointerButton {
+            pos: pos2(10.0, 20.0),
+            button: PointerButton::Primary,
+            pressed,
+            modifiers: Modifiers::SHIFT,
+        };
+        assert_eq!(
+            input.events,
+            [
+                Event::PointerMoved(pos2(10.0, 20.0)),
+                button(true),
+                button(false),
+                Event::Text("hi".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn pointer_events_outside_are_clipped() {
+        let rect = Rect::from_min_size(pos2(100.0, 50.0), Vec2::splat(20.0));
+        let mut input = RawInput {
+            events: vec![
+                Event::PointerMoved(pos2(110.0, 60.0)),
+                button(pos2(110.0, 60.0), true),
+                Event::PointerMoved(pos2(90.0, 60.0)),
+                button(pos2(90.0, 60.0), true),
+                button(pos2(130.0, 60.0), false),
+                Event::Text("a".into()),
+            ],
+            ..Default::default()
+        };
+
+        remap_raw_input(&mut input, rect);
+        clip_pointer_events(&mut input, Rect::from_min_size(Pos2::ZERO, rect.size()));
+        assert_eq!(
+            input.events,
+            [
+                Event::PointerMoved(pos2(10.0, 10.0)),
+                button(pos2(10.0, 10.0), true),
+                Event::PointerGone,
+                // The press outside is dropped, but the release still ends the drag
+                button(pos2(30.0, 10.0), false),
+                Event::Text("a".into()),
+            ]
+        );
+    }
+}
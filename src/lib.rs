@@ -1,10 +1,101 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicUsize};
 
 use egui::{
-    epaint, ClippedPrimitive, Color32, Rgba, TextureFilter, TextureId, TextureOptions,
-    TextureWrapMode, TexturesDelta,
+    epaint, ClippedPrimitive, Rgba, TextureFilter, TextureId, TextureOptions, TextureWrapMode,
+    TexturesDelta,
 };
-use euc::{Buffer2d, CullMode, Pipeline, Sampler, Target, Texture, TriangleList};
+#[cfg(not(feature = "fixed-point"))]
+use euc::Sampler;
+use euc::{Buffer2d, CullMode, Empty, Pipeline, Target, Texture, TriangleList};
+
+mod accumulate;
+mod color;
+mod debug;
+mod diff;
+mod dither;
+mod error;
+#[cfg(feature = "fixed-point")]
+mod fixed;
+mod input;
+mod occlusion;
+mod postprocess;
+mod sampler;
+mod scratch;
+mod stats;
+mod target;
+#[cfg(feature = "test-util")]
+mod test_util;
+mod texture;
+mod transform;
+mod validate;
+#[cfg(feature = "web")]
+mod web;
+#[cfg(feature = "winit")]
+mod winit_input;
+
+use color::over;
+pub use color::{
+    pack_pixel, pack_pixel_dithered, premultiply, premultiply_u32, unpack_pixel, unpremultiply,
+    unpremultiply_u32, BlendOp,
+};
+pub use debug::{composite_over_checkerboard, DebugMode};
+#[cfg(feature = "std")]
+pub use debug::{debug_print_buffer, write_buffer_preview};
+pub use diff::TileId;
+pub use error::PainterError;
+pub use input::{clip_pointer_events, remap_raw_input, HeadlessInput};
+use occlusion::Occluded;
+pub use postprocess::{ColorBlindness, PostProcess};
+pub use sampler::{Bilinear, Bordered, SrgbBilinear, UvSampler};
+use stats::CountWrites;
+pub use stats::PaintStats;
+pub use target::{RenderTarget, Rgb888Target};
+#[cfg(feature = "test-util")]
+pub use test_util::{assert_buffer_matches, render_demo};
+pub use texture::{Alpha8Texture, PackedTexture, Rgb565A8Texture, Rgba4444Texture, TextureStorage};
+use texture::{SoftwareTexture, TexturePixels};
+pub use transform::Affine2;
+pub use validate::{validate_mesh, MeshError};
+#[cfg(feature = "web")]
+pub use web::buffer_to_web_image_data;
+#[cfg(feature = "winit")]
+pub use winit_input::WinitInput;
+
+/// Optional features of this crate compiled in, see [`features`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Features {
+    /// Meshes are rasterized on multiple threads (`par`).
+    pub par: bool,
+    /// Frames can be previewed in the terminal (`std`).
+    pub std: bool,
+    /// `DrawRecord`s of painted meshes are kept (`draw-records`).
+    pub draw_records: bool,
+    /// Helpers for HTML canvases are available (`web`).
+    pub web: bool,
+    /// Blending and nearest texture sampling use integer math instead of floating point
+    /// (`fixed-point`).
+    pub fixed_point: bool,
+    /// Frame comparison assertions and the demo frame are available (`test-util`).
+    pub test_util: bool,
+    /// Translation of winit events is available (`winit`).
+    pub winit: bool,
+}
+
+/// The optional features this crate was compiled with, e.g. for a framework built on top of it to
+/// fall back to other code paths at runtime.
+pub const fn features() -> Features {
+    Features {
+        par: cfg!(feature = "par"),
+        std: cfg!(feature = "std"),
+        draw_records: cfg!(feature = "draw-records"),
+        web: cfg!(feature = "web"),
+        fixed_point: cfg!(feature = "fixed-point"),
+        test_util: cfg!(feature = "test-util"),
+        winit: cfg!(feature = "winit"),
+    }
+}
 
 /// Egui vertex data which is algebraic (has Mul and Add)
 #[derive(Clone, Copy, Debug)]
@@ -43,10 +134,38 @@ impl From<epaint::Vertex> for EguiVertexData {
 }
 
 /// Euc Pipeline which can draw an egui mesh, using `sampler` as a texture.
+///
+/// UVs are interpolated unwrapped and only brought into range by the sampler, per fragment. A single
+/// quad with UVs far outside of `[0, 1]` therefore tiles seamlessly under `TextureWrapMode::Repeat`,
+/// and clipping (which only discards writes, see [`Scissor`]) does not shift the tiling.
 pub struct EguiMeshEucPipeline<'r, S> {
     pub sampler: S,
     pub vertices: &'r [epaint::Vertex],
     pub screen_size_points: egui::Vec2,
+    /// Gamma applied to the alpha of each texture sample, see [`Painter::set_text_gamma`]. `1.0`
+    /// leaves samples unchanged.
+    pub coverage_gamma: f32,
+    /// Multiplier applied to every fragment, see [`Painter::set_global_opacity`].
+    pub opacity: f32,
+    /// Applied to every fragment just before blending, see [`Painter::set_fragment_hook`].
+    pub fragment_hook: Option<&'r FragmentHook>,
+    /// How fragments are combined with the target, see [`Painter::set_blend_op`].
+    pub blend_op: BlendOp,
+    /// Applied to vertex positions before they are mapped to the target, see
+    /// [`Painter::set_logical_offset`] and [`Painter::set_transform_around`].
+    pub transform: Affine2,
+}
+
+/// A function transforming each fragment's linear premultiplied color.
+pub type FragmentHook = dyn Fn(Rgba) -> Rgba + Send + Sync;
+
+/// A function translating the texture ids of meshes before they are looked up, see
+/// [`Painter::set_texture_id_remap`].
+pub type TextureIdRemap = dyn Fn(TextureId) -> TextureId + Send + Sync;
+
+/// The id `id` is looked up as under `remap`.
+pub(crate) fn remap_texture_id(remap: Option<&TextureIdRemap>, id: TextureId) -> TextureId {
+    remap.map_or(id, |remap| remap(id))
 }
 
 pub fn egui_coord_to_ndc(pos: egui::Pos2, screen_size: egui::Vec2) -> [f32; 2] {
@@ -56,7 +175,7 @@ pub fn egui_coord_to_ndc(pos: egui::Pos2, screen_size: egui::Vec2) -> [f32; 2] {
 
 impl<'r, S> Pipeline<'r> for EguiMeshEucPipeline<'r, S>
 where
-S: Sampler<2, Index = f32, Sample = egui::Rgba>,
+    S: UvSampler,
 {
     type Vertex = u32;
     type VertexData = EguiVertexData;
@@ -67,25 +186,36 @@ S: Sampler<2, Index = f32, Sample = egui::Rgba>,
     #[inline(always)]
     fn vertex(&self, idx: &Self::Vertex) -> ([f32; 4], Self::VertexData) {
         let vertex = self.vertices[*idx as usize];
-        let [x, y] = egui_coord_to_ndc(vertex.pos, self.screen_size_points);
+        let [x, y] = egui_coord_to_ndc(self.transform.apply(vertex.pos), self.screen_size_points);
         let xyzw = [x, y, 0.0, 1.0];
         (xyzw, vertex.into())
     }
 
     #[inline(always)]
     fn fragment(&self, color: Self::VertexData) -> Self::Fragment {
-        color.color * self.sampler.sample([color.uv.x, color.uv.y])
+        let mut sample = self.sampler.sample_uv([color.uv.x, color.uv.y]);
+        if self.coverage_gamma != 1.0 {
+            sample = apply_coverage_gamma(sample, self.coverage_gamma);
+        }
+        // Multiplying two premultiplied colors yields the premultiplied product of the straight
+        // colors, since (c * a) * (s * b) = (c * s) * (a * b). This matches egui's GPU shaders
+        let fragment = color.color * sample * self.opacity;
+        match self.fragment_hook {
+            Some(hook) => hook(fragment),
+            None => fragment,
+        }
     }
 
     fn blend(&self, screen: Self::Pixel, fragment: Self::Fragment) -> Self::Pixel {
-        let [r, g, b, a] = screen.to_le_bytes();
-        let screen = Color32::from_rgba_premultiplied(r, g, b, a);
-        let screen: Rgba = screen.into();
-
-        let mut color = fragment + screen * (1.0 - fragment.a());
-        color[3] = screen.a() + fragment.a() * (1.0 - screen.a());
-
-        u32::from_le_bytes(color.to_srgba_unmultiplied())
+        match self.blend_op {
+            #[cfg(feature = "fixed-point")]
+            BlendOp::Over => fixed::over(screen, fragment),
+            #[cfg(not(feature = "fixed-point"))]
+            BlendOp::Over => pack_pixel(over(unpack_pixel(screen), fragment)),
+            // Skips decoding the screen pixel
+            BlendOp::Replace => pack_pixel(fragment),
+            op => pack_pixel(op.blend(unpack_pixel(screen), fragment)),
+        }
     }
 
     fn rasterizer_config(&self) -> CullMode {
@@ -93,6 +223,57 @@ S: Sampler<2, Index = f32, Sample = egui::Rgba>,
     }
 }
 
+/// Rasterizes a single triangle with the same sampling and blending as egui meshes. Vertex
+/// positions are in pixels of `target`.
+pub fn rasterize_triangle<T, S>(target: &mut T, vertices: [epaint::Vertex; 3], sampler: S)
+where
+    T: Target<Texel = u32> + Send + Sync,
+    S: UvSampler + Send + Sync,
+{
+    let [width, height] = target.size();
+
+    EguiMeshEucPipeline {
+        sampler,
+        vertices: &vertices,
+        screen_size_points: egui::Vec2::new(width as f32, height as f32),
+        coverage_gamma: 1.0,
+        opacity: 1.0,
+        fragment_hook: None,
+        blend_op: BlendOp::Over,
+        transform: Affine2::IDENTITY,
+    }
+    .render(&[0u32, 1, 2], target, &mut Empty::<f32>::default());
+}
+
+/// Converts the indices of a triangle strip into the triangle list [`EguiMeshEucPipeline`] draws,
+/// e.g. to fill a custom [`epaint::Mesh`] with strip geometry. Every other triangle is flipped so
+/// all keep the winding of the first, and degenerate triangles, which strips use to restart, are
+/// dropped.
+pub fn triangle_strip_to_list(strip: &[u32]) -> Vec<u32> {
+    let mut list = Vec::with_capacity(strip.len().saturating_sub(2) * 3);
+    for (i, triangle) in strip.windows(3).enumerate() {
+        let [a, b, c] = [triangle[0], triangle[1], triangle[2]];
+        if a == b || b == c || a == c {
+            continue;
+        }
+        if i % 2 == 0 {
+            list.extend([a, b, c]);
+        } else {
+            list.extend([b, a, c]);
+        }
+    }
+    list
+}
+
+/// Raises the alpha of a premultiplied `sample` to `1 / gamma`, scaling its color along with it.
+fn apply_coverage_gamma(sample: Rgba, gamma: f32) -> Rgba {
+    let alpha = sample.a();
+    if alpha <= 0.0 {
+        return sample;
+    }
+    sample * (alpha.powf(gamma.recip()) / alpha)
+}
+
 /// Wrapper of a euc::Target, reads are unaffected but writes are clipped
 /// by the given rectangle. 
 pub struct Scissor<T> {
@@ -114,21 +295,37 @@ impl<T> Scissor<T> {
         }
     }
 
+    /// Shrinks the scissor rectangle to its intersection with `[x, y, width, height]`.
+    fn restrict(mut self, [x, y, width, height]: [usize; 4]) -> Self {
+        let min_x = self.x.max(x);
+        let min_y = self.y.max(y);
+        let max_x = (self.x + self.width).min(x + width).max(min_x);
+        let max_y = (self.y + self.height).min(y + height).max(min_y);
+        self.x = min_x;
+        self.y = min_y;
+        self.width = max_x - min_x;
+        self.height = max_y - min_y;
+        self
+    }
+
+    #[inline(always)]
     fn bounds_check(&self, x: usize, y: usize) -> bool {
-        x >= self.x && y >= self.y && x < self.x + self.width && y < self.y + self.height
+        // Coordinates left of/above the rectangle wrap around to huge values, folding both bounds
+        // of each axis into one comparison.
+        x.wrapping_sub(self.x) < self.width && y.wrapping_sub(self.y) < self.height
     }
 
     fn from_clip_rect(
         inner: T,
         [width_px, height_px]: [usize; 2],
-        pixels_per_point: f32,
+        pixel_scale: egui::Vec2,
         clip_rect: egui::Rect,
     ) -> Self {
         // Transform clip rect to physical pixels:
-        let clip_min_x = pixels_per_point * clip_rect.min.x;
-        let clip_min_y = pixels_per_point * clip_rect.min.y;
-        let clip_max_x = pixels_per_point * clip_rect.max.x;
-        let clip_max_y = pixels_per_point * clip_rect.max.y;
+        let clip_min_x = pixel_scale.x * clip_rect.min.x;
+        let clip_min_y = pixel_scale.y * clip_rect.min.y;
+        let clip_max_x = pixel_scale.x * clip_rect.max.x;
+        let clip_max_y = pixel_scale.y * clip_rect.max.y;
 
         // Round to integer:
         let clip_min_x = clip_min_x.round() as i32;
@@ -152,6 +349,51 @@ impl<T> Scissor<T> {
     }
 }
 
+impl<T: Texture<2, Index = usize>> Scissor<T> {
+    /// Like [`Scissor::new`], but fails if the rectangle doesn't fit inside of `inner`.
+    pub fn try_new(
+        inner: T,
+        x: usize,
+        y: usize,
+        width: usize,
+        height: usize,
+    ) -> Result<Self, ScissorError> {
+        let size = inner.size();
+        let fits = x.checked_add(width).is_some_and(|right| right <= size[0])
+            && y.checked_add(height)
+                .is_some_and(|bottom| bottom <= size[1]);
+        if fits {
+            Ok(Self::new(inner, x, y, width, height))
+        } else {
+            Err(ScissorError {
+                rect: [x, y, width, height],
+                size,
+            })
+        }
+    }
+}
+
+/// A scissor rectangle exceeding its target, see [`Scissor::try_new`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScissorError {
+    /// The rectangle, as `[x, y, width, height]`.
+    pub rect: [usize; 4],
+    /// The size of the target.
+    pub size: [usize; 2],
+}
+
+impl std::fmt::Display for ScissorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let ([x, y, width, height], [target_width, target_height]) = (self.rect, self.size);
+        write!(
+            f,
+            "scissor rectangle {width}x{height} at ({x}, {y}) exceeds the {target_width}x{target_height} target"
+        )
+    }
+}
+
+impl std::error::Error for ScissorError {}
+
 impl<T, const N: usize> Texture<N> for Scissor<T>
 where
     T: Texture<N>,
@@ -182,221 +424,2202 @@ impl<T: Target> Target for Scissor<T> {
     }
 }
 
-struct SoftwareTexture {
-    pixels: euc::Buffer2d<egui::Rgba>,
-    options: egui::TextureOptions,
+/// Wrapper of a euc::Target which clips writes by a rectangle like [`Scissor`], but fades them in
+/// over `feather` pixels inside of its edges instead of cutting them off sharply. Each write is
+/// mixed with the pixel it replaces, by how far the pixel's center is from the nearest edge
+/// relative to `feather`. For [`BlendOp::Over`] this is exactly the same as attenuating the alpha
+/// of the fragment.
+pub struct FeatheredScissor<T> {
+    pub inner: T,
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+    pub feather: f32,
+}
+
+impl<T> FeatheredScissor<T> {
+    /// Clips writes to `inner` by `[x, y, width, height]`, fading over `feather` pixels.
+    pub fn new(inner: T, [x, y, width, height]: [usize; 4], feather: f32) -> Self {
+        Self {
+            inner,
+            x,
+            y,
+            width,
+            height,
+            feather,
+        }
+    }
+
+    /// How much of a write to pixel `[x, y]` is kept, from `0.0` to `1.0`.
+    #[inline(always)]
+    fn coverage(&self, x: usize, y: usize) -> f32 {
+        if x.wrapping_sub(self.x) >= self.width || y.wrapping_sub(self.y) >= self.height {
+            return 0.0;
+        }
+        if self.feather <= 0.0 {
+            return 1.0;
+        }
+        let (center_x, center_y) = (x as f32 + 0.5, y as f32 + 0.5);
+        let distance = (center_x - self.x as f32)
+            .min((self.x + self.width) as f32 - center_x)
+            .min(center_y - self.y as f32)
+            .min((self.y + self.height) as f32 - center_y);
+        (distance / self.feather).min(1.0)
+    }
+}
+
+impl<T, const N: usize> Texture<N> for FeatheredScissor<T>
+where
+    T: Texture<N>,
+{
+    type Index = T::Index;
+    type Texel = T::Texel;
+
+    fn size(&self) -> [Self::Index; N] {
+        self.inner.size()
+    }
+
+    fn read(&self, index: [Self::Index; N]) -> Self::Texel {
+        self.inner.read(index)
+    }
+}
+
+impl<T: Target<Texel = u32>> Target for FeatheredScissor<T> {
+    unsafe fn read_exclusive_unchecked(&self, x: usize, y: usize) -> Self::Texel {
+        unsafe { self.inner.read_exclusive_unchecked(x, y) }
+    }
+
+    unsafe fn write_exclusive_unchecked(&self, x: usize, y: usize, texel: Self::Texel) {
+        let coverage = self.coverage(x, y);
+        if coverage <= 0.0 {
+            return;
+        }
+        let texel = if coverage < 1.0 {
+            let old = unpack_pixel(unsafe { self.inner.read_exclusive_unchecked(x, y) });
+            pack_pixel(old * (1.0 - coverage) + unpack_pixel(texel) * coverage)
+        } else {
+            texel
+        };
+        unsafe {
+            self.inner.write_exclusive_unchecked(x, y, texel);
+        }
+    }
+}
+
+/// Wrapper of a euc::Target exposing only a sub-rectangle of it, offset so that its top-left
+/// corner is `[0, 0]`. Reads and writes both stay inside of the rectangle, so one large buffer can
+/// hold several render targets which are later sampled from separately.
+pub struct RegionView<T> {
+    inner: T,
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl<T: Texture<2, Index = usize>> RegionView<T> {
+    /// Views the `[x, y, width, height]` rectangle of `inner`.
+    ///
+    /// # Panics
+    /// If the rectangle exceeds the bounds of `inner`.
+    pub fn new(inner: T, [x, y, width, height]: [usize; 4]) -> Self {
+        let [inner_width, inner_height] = inner.size();
+        assert!(
+            x + width <= inner_width && y + height <= inner_height,
+            "Region exceeds the bounds of the target"
+        );
+        Self {
+            inner,
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T> Texture<2> for RegionView<T>
+where
+    T: Texture<2, Index = usize>,
+{
+    type Index = usize;
+    type Texel = T::Texel;
+
+    fn size(&self) -> [Self::Index; 2] {
+        [self.width, self.height]
+    }
+
+    fn read(&self, [x, y]: [Self::Index; 2]) -> Self::Texel {
+        assert!(x < self.width && y < self.height, "Read outside of region");
+        self.inner.read([self.x + x, self.y + y])
+    }
+}
+
+impl<T: Target> Target for RegionView<T> {
+    unsafe fn read_exclusive_unchecked(&self, x: usize, y: usize) -> Self::Texel {
+        unsafe { self.inner.read_exclusive_unchecked(self.x + x, self.y + y) }
+    }
+
+    unsafe fn write_exclusive_unchecked(&self, x: usize, y: usize, texel: Self::Texel) {
+        unsafe {
+            self.inner
+                .write_exclusive_unchecked(self.x + x, self.y + y, texel);
+        }
+    }
+}
+
+/// Description of a single mesh drawn by [`Painter`], recorded for debugging.
+#[cfg(feature = "draw-records")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct DrawRecord {
+    pub texture_id: TextureId,
+    pub clip_rect: egui::Rect,
+    pub triangle_count: usize,
+    pub filter: TextureFilter,
+    pub wrap_mode: TextureWrapMode,
 }
 
 /// A persistent object which tracks textures and can render an image from clipped primitives.
+///
+/// Textures are kept by id across frames, from the [`TexturesDelta`] which sets them until the one
+/// which frees them, so a texture uploaded once is sampled by every later frame referencing its id.
 pub struct Painter {
+    /// Only changed by texture uploads and frees, never cleared between frames.
     textures: HashMap<TextureId, SoftwareTexture>,
+    /// Version of each allocated texture, bumped whenever anything changes how it samples, so
+    /// retained frames can tell which textures changed since they were drawn.
+    texture_versions: HashMap<TextureId, u64>,
+    /// Last version handed out to any texture; versions are never reused, even across frees.
+    last_texture_version: u64,
+    texture_storage: TextureStorage,
+    /// Storage of particular textures, see [`Painter::set_texture_storage_of`].
+    texture_storages: HashMap<TextureId, TextureStorage>,
+    max_texture_size: usize,
+    /// See [`Painter::set_upload_budget`].
+    upload_budget: Option<usize>,
+    /// Texture deltas deferred to later frames by the upload budget, in order.
+    pending_uploads: VecDeque<(TextureId, epaint::ImageDelta)>,
+    drew_visible_pixels: bool,
+    text_gamma: f32,
+    /// Textures whose uploads hold linear color, see [`Painter::set_texture_linear`].
+    linear_textures: HashSet<TextureId>,
+    /// Textures holding glyphs, which [`Painter::set_text_gamma`] applies to.
+    font_textures: Vec<TextureId>,
+    opacity: f32,
+    fragment_hook: Option<Box<FragmentHook>>,
+    texture_id_remap: Option<Box<TextureIdRemap>>,
+    blend_op: BlendOp,
+    /// See [`Painter::set_dither`].
+    dither: bool,
+    occlusion_culling: bool,
+    /// See [`Painter::set_clip_feather`].
+    clip_feather: f32,
+    logical_offset: egui::Vec2,
+    /// Applied to content before the logical offset, see [`Painter::set_transform_around`].
+    transform: Affine2,
+    /// See [`Painter::set_mirror`].
+    mirror: [bool; 2],
+    /// Shifts the level of detail textures are filtered at, see [`Painter::set_lod_bias`].
+    lod_bias: f32,
+    /// Whether linear filtering interpolates sRGB, see [`Painter::set_srgb_filtering`].
+    srgb_filtering: bool,
+    /// Width of an output pixel relative to its height, see [`Painter::set_pixel_aspect_ratio`].
+    pixel_aspect_ratio: f32,
+    post_process: Option<PostProcess>,
+    /// Rectangle every primitive is clipped to, see [`Painter::set_viewport`].
+    viewport: Option<[usize; 4]>,
+    /// Textures to free at the end of the current frame.
+    pending_frees: Vec<TextureId>,
+    /// See [`Painter::last_errors`].
+    errors: Vec<PainterError>,
+    stats: PaintStats,
+    count_pixels: bool,
+    /// See [`Painter::set_degenerate_triangle_area`].
+    degenerate_triangle_area: f32,
+    /// Quantized UV sampled at each pixel, see [`Painter::set_record_uvs`].
+    uv_buffer: Option<Buffer2d<u32>>,
+    debug_mode: DebugMode,
+    buffer_count: usize,
+    scratch: scratch::Scratch,
+    retained: Option<diff::RetainedFrame>,
+    accumulation: Option<accumulate::Accumulation>,
+    #[cfg(feature = "draw-records")]
+    draw_records: Vec<DrawRecord>,
 }
 
 impl Painter {
     pub fn new() -> Self {
         Self {
             textures: HashMap::new(),
+            texture_versions: HashMap::new(),
+            last_texture_version: 0,
+            texture_storage: TextureStorage::default(),
+            texture_storages: HashMap::new(),
+            max_texture_size: usize::MAX,
+            upload_budget: None,
+            pending_uploads: VecDeque::new(),
+            drew_visible_pixels: false,
+            text_gamma: 1.0,
+            linear_textures: HashSet::new(),
+            // egui always puts the font atlas at the default texture id
+            font_textures: vec![TextureId::default()],
+            opacity: 1.0,
+            fragment_hook: None,
+            texture_id_remap: None,
+            blend_op: BlendOp::Over,
+            dither: false,
+            occlusion_culling: false,
+            clip_feather: 0.0,
+            logical_offset: egui::Vec2::ZERO,
+            transform: Affine2::IDENTITY,
+            mirror: [false; 2],
+            lod_bias: 0.0,
+            srgb_filtering: false,
+            pixel_aspect_ratio: 1.0,
+            post_process: None,
+            viewport: None,
+            pending_frees: Vec::new(),
+            errors: Vec::new(),
+            stats: PaintStats::default(),
+            count_pixels: false,
+            degenerate_triangle_area: 1e-4,
+            uv_buffer: None,
+            debug_mode: DebugMode::default(),
+            buffer_count: 1,
+            scratch: Default::default(),
+            retained: None,
+            accumulation: None,
+            #[cfg(feature = "draw-records")]
+            draw_records: Vec::new(),
         }
     }
 
-    pub fn paint_and_update_textures(
-        &mut self,
-        textures_delta: &TexturesDelta,
-        clipped_primitives: &[ClippedPrimitive],
-        pixels_per_point: f32,
-        screen_size: [usize; 2],
-    ) -> euc::Buffer2d<u32> {
-        self.allocate_textures(textures_delta);
+    /// Like [`Painter::new`], with room for `capacity` textures before the texture table needs to
+    /// grow, e.g. when the font atlas and many images all arrive in the first frame.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            textures: HashMap::with_capacity(capacity),
+            texture_versions: HashMap::with_capacity(capacity),
+            ..Self::new()
+        }
+    }
 
-        let image = self.render(clipped_primitives, pixels_per_point, screen_size);
+    /// Sets how textures uploaded from now on are stored. Existing textures keep their storage
+    /// until their next whole upload.
+    pub fn set_texture_storage(&mut self, storage: TextureStorage) {
+        if storage != self.texture_storage {
+            // Retained pixels may sample textures stored the old way
+            self.retained = None;
+        }
+        self.texture_storage = storage;
+    }
 
-        self.free_textures(textures_delta);
+    /// Overrides how texture `id` is stored from its next whole upload on, e.g. to keep the font
+    /// atlas as [`TextureStorage::Alpha8`] while images use full color. `None` goes back to the
+    /// storage set by [`Painter::set_texture_storage`].
+    pub fn set_texture_storage_of(&mut self, id: TextureId, storage: Option<TextureStorage>) {
+        match storage {
+            Some(storage) => self.texture_storages.insert(id, storage),
+            None => self.texture_storages.remove(&id),
+        };
+    }
 
-        image
+    /// Limits the size textures uploaded from now on are stored at. Larger textures are box filtered
+    /// down so neither side exceeds `max_size`, and sampled at the lower resolution. Existing
+    /// textures keep their size until their next whole upload.
+    pub fn set_max_texture_size(&mut self, max_size: usize) {
+        if max_size != self.max_texture_size {
+            // Retained pixels may sample textures stored at the old size
+            self.retained = None;
+        }
+        self.max_texture_size = max_size;
     }
 
-    fn allocate_textures(&mut self, textures_delta: &TexturesDelta) {
-        for (id, delta) in &textures_delta.set {
-            if let Some(texture) = self.textures.get_mut(id) {
-                texture.update(delta);
-            } else {
-                if delta.is_whole() {
-                    self.textures.insert(
-                        id.clone(),
-                        SoftwareTexture::new(delta.image.clone(), delta.options),
-                    );
-                } else {
-                    panic!("Attempted partial update on absent texture")
-                }
-            }
+    /// Limits the texture data uploaded per frame to `bytes_per_frame`, counting 4 bytes per texel
+    /// of each delta, or lifts the limit with `None`. Deltas beyond the budget are deferred to
+    /// later frames in order, and large ones are split by rows. This trades hitches for tearing:
+    /// textures are drawn with whatever has arrived, so a new texture starts out transparent and
+    /// fills in from the top, and an updated one mixes old and new rows until it is done. At
+    /// least one row is uploaded every frame, so uploads always finish. Defaults to `None`.
+    pub fn set_upload_budget(&mut self, bytes_per_frame: Option<usize>) {
+        self.upload_budget = bytes_per_frame;
+    }
+
+    /// Whether texture deltas are waiting for later frames under [`Painter::set_upload_budget`],
+    /// in which case the GUI should keep being repainted until they have arrived.
+    pub fn has_pending_uploads(&self) -> bool {
+        !self.pending_uploads.is_empty()
+    }
+
+    /// Sets the gamma applied to glyph coverage when sampling the font atlas. Values above `1.0`
+    /// make text heavier, e.g. to match the perceived weight of GPU-rendered text. Defaults to `1.0`.
+    pub fn set_text_gamma(&mut self, gamma: f32) {
+        if gamma != self.text_gamma {
+            // Retained pixels show text at the old gamma
+            self.retained = None;
         }
+        self.text_gamma = gamma;
     }
 
-    fn free_textures(&mut self, textures_delta: &TexturesDelta) {
-        for id in &textures_delta.free {
-            self.textures.remove(id);
+    /// Marks `id` as another page of glyphs besides egui's font atlas, so text drawn from it gets
+    /// the gamma set by [`Painter::set_text_gamma`] too. Any number of textures can be drawn from in
+    /// the same frame either way; this only affects the gamma.
+    pub fn add_font_texture(&mut self, id: TextureId) {
+        if !self.font_textures.contains(&id) {
+            // Retained pixels show the texture without the text gamma
+            self.retained = None;
+            self.font_textures.push(id);
         }
     }
 
-    fn render(
-        &mut self,
-        clipped_primitives: &[ClippedPrimitive],
-        pixels_per_point: f32,
-        screen_size: [usize; 2],
-    ) -> Buffer2d<u32> {
-        let mut color = Buffer2d::fill(screen_size, 0);
-        let mut depth = Buffer2d::fill(screen_size, 1.0);
+    /// Sets an opacity every fragment is multiplied by, e.g. to fade the whole GUI in or out without
+    /// re-tessellating. Translucent content becomes proportionally more translucent. Defaults to `1.0`.
+    pub fn set_global_opacity(&mut self, opacity: f32) {
+        let opacity = opacity.clamp(0.0, 1.0);
+        if opacity != self.opacity {
+            // Retained pixels are drawn at the old opacity
+            self.retained = None;
+        }
+        self.opacity = opacity;
+    }
 
-        for item in clipped_primitives {
-            if let epaint::Primitive::Mesh(mesh) = &item.primitive {
-                let mut scissor = Scissor::from_clip_rect(
-                    &mut color,
-                    screen_size,
-                    pixels_per_point,
-                    item.clip_rect,
-                );
+    /// Sets a function applied to the linear premultiplied color of every fragment before it is
+    /// blended, for effects like tinting or inverting the whole GUI.
+    pub fn set_fragment_hook(&mut self, hook: impl Fn(Rgba) -> Rgba + Send + Sync + 'static) {
+        // Retained pixels went through the old hook, if any
+        self.retained = None;
+        self.fragment_hook = Some(Box::new(hook));
+    }
 
-                let texture = self
-                    .textures
-                    .get(&mesh.texture_id)
-                    .expect("Mesh referenced absent texture");
+    /// Sets how fragments are combined with what is already drawn. Defaults to [`BlendOp::Over`].
+    ///
+    /// [`BlendOp::Replace`] writes fragments without decoding the pixels below and blending them,
+    /// which is faster but only looks right if everything drawn is opaque, e.g. solid themes
+    /// without shadows. Anti-aliased edges then lose their smoothing.
+    pub fn set_blend_op(&mut self, blend_op: BlendOp) {
+        if blend_op != self.blend_op {
+            // Retained pixels were blended the old way
+            self.retained = None;
+        }
+        self.blend_op = blend_op;
+    }
 
-                let pixels = &texture.pixels;
+    /// Sets whether drawn pixels are quantized to 8 bits with [`pack_pixel_dithered`] instead of
+    /// [`pack_pixel`], trading the bands of smooth gradients for fine noise. Each mesh is dithered
+    /// as it is blended, so the pattern is fixed to output pixels and doesn't move with content.
+    /// Debug overlays aren't dithered. Defaults to `false`.
+    pub fn set_dither(&mut self, dither: bool) {
+        if dither != self.dither {
+            // Retained pixels were quantized the other way
+            self.retained = None;
+        }
+        self.dither = dither;
+    }
 
-                let screen_size_points = egui::Vec2::new(screen_size[0] as f32, screen_size[1] as f32) / pixels_per_point;
+    /// Sets over how many pixels primitives fade out towards the edges of their clip rects, for
+    /// panels with soft edges instead of hard cuts, see [`FeatheredScissor`]. Disables occlusion
+    /// culling while above zero. Defaults to `0.0`, clipping sharply.
+    pub fn set_clip_feather(&mut self, feather: f32) {
+        if feather != self.clip_feather {
+            // Retained pixels fade out over the old width
+            self.retained = None;
+        }
+        self.clip_feather = feather;
+    }
 
-                // TODO: This dumb as HELL
-                match (texture.options.magnification, texture.options.wrap_mode) {
-                    (TextureFilter::Linear, TextureWrapMode::Repeat) => {
-                        EguiMeshEucPipeline {
-                            vertices: &mesh.vertices,
-                            sampler: pixels.linear().tiled(),
-                            screen_size_points,
-                        }
-                        .render(&mesh.indices, &mut scissor, &mut depth);
-                    }
-                    (TextureFilter::Linear, TextureWrapMode::ClampToEdge) => {
-                        EguiMeshEucPipeline {
-                            vertices: &mesh.vertices,
-                            sampler: pixels.linear().clamped(),
-                            screen_size_points,
-                        }
-                        .render(&mesh.indices, &mut scissor, &mut depth);
-                    }
-                    (TextureFilter::Linear, TextureWrapMode::MirroredRepeat) => {
-                        EguiMeshEucPipeline {
-                            vertices: &mesh.vertices,
-                            sampler: pixels.linear().mirrored(),
-                            screen_size_points,
-                        }
-                        .render(&mesh.indices, &mut scissor, &mut depth);
-                    }
-                    (TextureFilter::Nearest, TextureWrapMode::Repeat) => {
-                        EguiMeshEucPipeline {
-                            vertices: &mesh.vertices,
-                            sampler: pixels.nearest().tiled(),
-                            screen_size_points,
-                        }
-                        .render(&mesh.indices, &mut scissor, &mut depth);
-                    }
-                    (TextureFilter::Nearest, TextureWrapMode::ClampToEdge) => {
-                        EguiMeshEucPipeline {
-                            vertices: &mesh.vertices,
-                            sampler: pixels.nearest().clamped(),
-                            screen_size_points,
-                        }
-                        .render(&mesh.indices, &mut scissor, &mut depth);
-                    }
-                    (TextureFilter::Nearest, TextureWrapMode::MirroredRepeat) => {
-                        EguiMeshEucPipeline {
-                            vertices: &mesh.vertices,
-                            sampler: pixels.nearest().mirrored(),
-                            screen_size_points,
-                        }
-                        .render(&mesh.indices, &mut scissor, &mut depth);
-                    }
-                };
-            }
+    /// Sets whether pixels which opaque rectangles drawn later in the frame would cover are
+    /// skipped, along with primitives hidden under such a rectangle entirely. The output is the
+    /// same either way, but dense UIs with opaque panels have less overdraw. Only solid rectangle
+    /// meshes without rounding count as opaque, and nothing is skipped while a global opacity, a
+    /// fragment hook or an additive or multiplicative [`BlendOp`] is set. Defaults to `false`.
+    pub fn set_occlusion_culling(&mut self, occlusion_culling: bool) {
+        if occlusion_culling != self.occlusion_culling {
+            // Retained pixels are the same either way, but may be rounded differently
+            self.retained = None;
         }
+        self.occlusion_culling = occlusion_culling;
+    }
 
-        color
+    /// Confines all drawing to the `[x, y, width, height]` rectangle of the output, in pixels,
+    /// whatever the clip rects of the primitives are. `None` allows drawing anywhere again.
+    pub fn set_viewport(&mut self, viewport: Option<[usize; 4]>) {
+        if viewport != self.viewport {
+            // Retained pixels outside of the new viewport would be stale
+            self.retained = None;
+        }
+        self.viewport = viewport;
     }
-}
 
-impl SoftwareTexture {
-    pub fn new(image: epaint::ImageData, options: TextureOptions) -> Self {
-        let pixels = Buffer2d::fill([image.width(), image.height()], Rgba::RED);
+    /// Sets the logical point, in egui points, drawn at the top-left corner of the output, e.g. to
+    /// scroll content laid out on a larger canvas. Vertices and clip rects are both shifted.
+    /// Defaults to zero.
+    pub fn set_logical_offset(&mut self, offset: egui::Vec2) {
+        if offset != self.logical_offset {
+            // Retained pixels show the content at the old offset
+            self.retained = None;
+        }
+        self.logical_offset = offset;
+    }
 
-        let delta = epaint::ImageDelta::full(image, options);
+    /// Rotates the content by `rotation` radians (clockwise) and scales it by `scale`, both around
+    /// `pivot` in points, e.g. for spinning panels or zooming to the cursor. Clip rects become
+    /// their bounding box once transformed, so rotated content may spill past them slightly, and
+    /// occlusion culling is skipped. Replaces any previous transform.
+    pub fn set_transform_around(&mut self, pivot: egui::Pos2, rotation: f32, scale: f32) {
+        self.set_transform(Affine2::around(pivot, rotation, scale));
+    }
 
-        let mut inst = Self { pixels, options };
+    /// Removes the transform set by [`Painter::set_transform_around`].
+    pub fn clear_transform(&mut self) {
+        self.set_transform(Affine2::IDENTITY);
+    }
 
-        inst.update(&delta);
+    fn set_transform(&mut self, transform: Affine2) {
+        if transform != self.transform {
+            // Retained pixels show the content under the old transform
+            self.retained = None;
+        }
+        self.transform = transform;
+    }
 
-        inst
+    /// Mirrors the whole output horizontally and/or vertically, e.g. for teleprompters or
+    /// displays seen through a mirror. Unlike [`Painter::set_transform_around`] the mirror is
+    /// applied last, across the target's full width or height. egui still lays out and hit-tests
+    /// the unmirrored GUI, so pointer positions from a host must be mirrored the same way before
+    /// they reach egui: `x` becomes `width - x` (in points) when mirrored horizontally, and
+    /// likewise `y` vertically. Defaults to mirroring neither.
+    pub fn set_mirror(&mut self, horizontal: bool, vertical: bool) {
+        if [horizontal, vertical] != self.mirror {
+            // Retained pixels show the content the other way around
+            self.retained = None;
+        }
+        self.mirror = [horizontal, vertical];
     }
 
-    pub fn update(&mut self, delta: &epaint::ImageDelta) {
-        let epaint::ImageData::Color(patch) = &delta.image;
+    /// Maps positions in points to the positions they are drawn at, before scaling by
+    /// `pixel_scale` into a target of `screen_size` pixels.
+    fn to_target(&self, screen_size: [usize; 2], pixel_scale: egui::Vec2) -> Affine2 {
+        let screen_size_points =
+            egui::vec2(screen_size[0] as f32, screen_size[1] as f32) / pixel_scale;
+        self.transform
+            .then_translate(-self.logical_offset)
+            .then_mirror(self.mirror, screen_size_points)
+    }
 
-        if delta.is_whole() && patch.size != self.pixels.size() {
-            *self = Self::new(delta.image.clone(), delta.options);
-            return;
+    /// Shifts the level of detail at which textures switch from their magnification filter to their
+    /// minification filter, in powers of two like a GPU's LOD bias. Negative values keep the
+    /// magnification filter for more shrinking, e.g. `-1.0` until a texture is drawn at half its
+    /// size; positive values switch earlier. Defaults to `0.0`, switching as soon as a texture shrinks.
+    pub fn set_lod_bias(&mut self, lod_bias: f32) {
+        if lod_bias != self.lod_bias {
+            // Retained pixels may be filtered the old way
+            self.retained = None;
+        }
+        self.lod_bias = lod_bias;
+    }
+
+    /// Sets whether linearly filtered textures are interpolated in sRGB rather than linear color,
+    /// see [`SrgbBilinear`], to match GPU backends sampling through sRGB-unaware texture views.
+    /// Defaults to `false`.
+    pub fn set_srgb_filtering(&mut self, srgb_filtering: bool) {
+        if srgb_filtering != self.srgb_filtering {
+            // Retained pixels were interpolated the old way
+            self.retained = None;
         }
+        self.srgb_filtering = srgb_filtering;
+    }
 
-        self.options = delta.options;
+    /// Sets the width of an output pixel relative to its height, for displays with non-square
+    /// pixels. `pixels_per_point` then counts vertical pixels, and content is squeezed horizontally
+    /// to match, e.g. a square of 10 points at 1x spans 5 by 10 pixels at an aspect ratio of `2.0`.
+    /// Defaults to `1.0`.
+    pub fn set_pixel_aspect_ratio(&mut self, aspect_ratio: f32) {
+        if aspect_ratio != self.pixel_aspect_ratio {
+            // Retained pixels are laid out for the old aspect ratio
+            self.retained = None;
+        }
+        self.pixel_aspect_ratio = aspect_ratio;
+    }
 
-        let [off_x, off_y] = delta.pos.unwrap_or([0, 0]);
+    /// Pixels per point along each axis, see [`Painter::set_pixel_aspect_ratio`].
+    fn pixel_scale(&self, pixels_per_point: f32) -> egui::Vec2 {
+        egui::vec2(pixels_per_point / self.pixel_aspect_ratio, pixels_per_point)
+    }
 
-        for y in 0..delta.image.height() {
-            for x in 0..delta.image.width() {
-                let sample = patch[(x, y)];
-                self.pixels
-                    .write(x + off_x, y + off_y, sample.into());
-            }
+    /// Sets a color transform applied to the output once it is drawn, e.g. to check a UI for
+    /// accessibility headlessly. It is applied at the end of every paint call to everything in
+    /// reach of it, so when several [`Painter::paint`] calls draw into one buffer, set it only for
+    /// the last one. Defaults to `None`.
+    pub fn set_post_process(&mut self, post_process: Option<PostProcess>) {
+        if post_process != self.post_process {
+            // Retained pixels went through the old transform
+            self.retained = None;
         }
+        self.post_process = post_process;
     }
-}
 
-pub fn euc_to_egui_colorimage(euc: euc::Buffer2d<u32>) -> egui::ColorImage {
-    let pixels = euc.raw().iter().map(|px| {
-        let [r, g, b, a] = px.to_le_bytes();
-        egui::Color32::from_rgba_unmultiplied(r, g, b, a)
-    })
-    .collect();
-    egui::ColorImage::new(euc.size(), pixels)
-}
+    /// Removes the function set by [`Painter::set_fragment_hook`].
+    pub fn clear_fragment_hook(&mut self) {
+        if self.fragment_hook.is_some() {
+            // Retained pixels went through the hook
+            self.retained = None;
+        }
+        self.fragment_hook = None;
+    }
 
-/// Helper to provide an image given successive egui::RawInputs
-pub struct SoftwareGui {
-    pub egui_ctx: egui::Context,
-    pub software_render: Painter,
-}
+    /// Sets a function translating the texture id of every mesh before it is looked up, e.g. to
+    /// draw primitives tessellated by another context, whose ids differ from this painter's.
+    /// Texture uploads are unaffected.
+    pub fn set_texture_id_remap(
+        &mut self,
+        remap: impl Fn(TextureId) -> TextureId + Send + Sync + 'static,
+    ) {
+        // Retained pixels may sample textures by the old ids
+        self.retained = None;
+        self.texture_id_remap = Some(Box::new(remap));
+    }
 
-impl SoftwareGui {
-    pub fn new() -> Self {
-        Self {
-            egui_ctx: Default::default(),
-            software_render: Painter::new(),
+    /// Removes the function set by [`Painter::set_texture_id_remap`].
+    pub fn clear_texture_id_remap(&mut self) {
+        if self.texture_id_remap.is_some() {
+            // Retained pixels may sample textures by the remapped ids
+            self.retained = None;
         }
+        self.texture_id_remap = None;
     }
 
-    pub fn update(
+    /// Sets what debug information is drawn over the output. Defaults to [`DebugMode::None`].
+    pub fn set_debug_mode(&mut self, debug_mode: DebugMode) {
+        if debug_mode != self.debug_mode {
+            // Retained pixels show the old debug overlay
+            self.retained = None;
+        }
+        self.debug_mode = debug_mode;
+    }
+
+    /// Releases spare capacity held by the texture table and per-frame bookkeeping, e.g. after
+    /// moving from a heavy screen to a light one. Texture pixels are always stored at their exact
+    /// size, so they are unaffected.
+    pub fn shrink_to_fit(&mut self) {
+        self.textures.shrink_to_fit();
+        self.texture_versions.shrink_to_fit();
+        if let Some(retained) = &mut self.retained {
+            retained.shrink_to_fit();
+        }
+        #[cfg(feature = "draw-records")]
+        self.draw_records.shrink_to_fit();
+    }
+
+    /// Whether the last frame wrote any pixel that isn't fully transparent, e.g. to skip presenting
+    /// empty frames. Only what was drawn counts, not what the target held before: a background
+    /// passed to [`Painter::paint_over_background`] doesn't, and neither do pixels
+    /// [`Painter::paint_diff`] kept from the previous frame without redrawing them.
+    pub fn drew_visible_pixels(&self) -> bool {
+        self.drew_visible_pixels
+    }
+
+    /// Sets the area in square pixels at or below which triangles are skipped as degenerate, e.g.
+    /// those with collinear vertices which tessellation sometimes emits. They cover no pixel
+    /// centers in practice, and skipping them avoids dividing by their (almost) zero area when
+    /// interpolating. `0.0` only skips triangles without any area. Defaults to `1e-4`.
+    pub fn set_degenerate_triangle_area(&mut self, max_area: f32) {
+        if max_area != self.degenerate_triangle_area {
+            // Retained pixels may include or skip other triangles
+            self.retained = None;
+        }
+        self.degenerate_triangle_area = max_area;
+    }
+
+    /// Sets whether the UV sampled at each pixel is recorded while painting, for
+    /// [`Painter::sampled_uv_at`], e.g. to find which glyph of the font atlas a broken pixel of text
+    /// came from. Every mesh is rasterized a second time to record them. Defaults to `false`.
+    pub fn set_record_uvs(&mut self, record_uvs: bool) {
+        if record_uvs != self.uv_buffer.is_some() {
+            // Reallocated to the right size by the next paint
+            self.uv_buffer = record_uvs.then(|| Buffer2d::fill([0, 0], debug::NO_UV));
+        }
+    }
+
+    /// The UV sampled at pixel `[x, y]` by the last mesh covering it, as of the last paint, clamped
+    /// to `[0, 1]` and quantized to steps of `1 / 65534`. `None` if no mesh covered the pixel, it
+    /// is outside of the last frame, or [`Painter::set_record_uvs`] is off.
+    pub fn sampled_uv_at(&self, x: usize, y: usize) -> Option<egui::Pos2> {
+        let uv_buffer = self.uv_buffer.as_ref()?;
+        let [width, height] = uv_buffer.size();
+        if x >= width || y >= height {
+            return None;
+        }
+        debug::dequantize_uv(uv_buffer.read([x, y]))
+    }
+
+    /// Input skipped since the last [`Painter::begin_frame`] (which every painting method starts its
+    /// frame with), in the order it was encountered. Painting goes on without it, so this is only
+    /// needed to find out why something is missing.
+    pub fn last_errors(&self) -> &[PainterError] {
+        &self.errors
+    }
+
+    /// The meshes [`Painter::last_errors`] reports as invalid, by their index among the primitives
+    /// of their paint call, e.g. to log which widget emitted a broken mesh.
+    pub fn last_mesh_errors(&self) -> impl Iterator<Item = (usize, MeshError)> + '_ {
+        self.errors.iter().filter_map(|error| match *error {
+            PainterError::InvalidMesh { primitive, error } => Some((primitive, error)),
+            _ => None,
+        })
+    }
+
+    /// Counts of the work done painting the last frame.
+    pub fn paint_stats(&self) -> PaintStats {
+        self.stats
+    }
+
+    /// Sets whether [`PaintStats::pixels_written`] is counted, which costs an atomic increment per
+    /// pixel written. Defaults to `false`.
+    pub fn set_count_pixels(&mut self, count_pixels: bool) {
+        self.count_pixels = count_pixels;
+    }
+
+    /// The meshes drawn during the last frame, in draw order.
+    #[cfg(feature = "draw-records")]
+    pub fn draw_records(&self) -> &[DrawRecord] {
+        &self.draw_records
+    }
+
+    /// Makes `pixels` (linear premultiplied color) available to meshes referencing `id`, replacing
+    /// any texture already there. Intended for [`TextureId::User`] ids, for images which don't go
+    /// through egui's texture manager.
+    ///
+    /// Pixels are stored as given, so HDR values outside of `[0, 1]` are preserved until the final
+    /// conversion to 8-bit output.
+    pub fn register_user_texture(
         &mut self,
-        new_input: egui::RawInput,
-        screen_size: [usize; 2],
-        sub_gui: impl FnMut(&egui::Context),
-    ) -> egui::ColorImage {
+        id: TextureId,
+        pixels: Buffer2d<Rgba>,
+        options: TextureOptions,
+    ) {
+        self.textures
+            .insert(id, SoftwareTexture::from_pixels(pixels, options));
+        self.texture_changed(id);
+    }
 
-        let output = self.egui_ctx.run(new_input, sub_gui);
-        let pixels_per_point = self.egui_ctx.pixels_per_point();
-        let clipped_primitives = self.egui_ctx.tessellate(output.shapes, pixels_per_point);
-        let buffer = self.software_render.paint_and_update_textures(
-            &output.textures_delta,
-            &clipped_primitives,
+    /// Sets whether the [`Color32`](egui::Color32)s uploaded to texture `id` hold linear color,
+    /// which is then used as is instead of being decoded from sRGB a second time. Applies to
+    /// uploads after this call, so set it before the texture is first uploaded.
+    pub fn set_texture_linear(&mut self, id: TextureId, linear: bool) {
+        if linear {
+            self.linear_textures.insert(id);
+        } else {
+            self.linear_textures.remove(&id);
+        }
+        if let Some(texture) = self.textures.get_mut(&id) {
+            texture.set_linear(linear);
+        }
+    }
+
+    /// Makes texture `id` return `border` (linear premultiplied) when sampled outside of `[0, 1]`,
+    /// overriding its wrap mode, or restores the wrap mode when `None`. Kept across updates of the
+    /// texture until it is freed. Does nothing if no such texture is allocated.
+    pub fn set_texture_border(&mut self, id: TextureId, border: Option<Rgba>) {
+        if let Some(texture) = self.textures.get_mut(&id) {
+            texture.border = border;
+            self.texture_changed(id);
+        }
+    }
+
+    /// Records that texture `id` was allocated or that its pixels or sampling changed.
+    fn texture_changed(&mut self, id: TextureId) {
+        self.last_texture_version += 1;
+        self.texture_versions.insert(id, self.last_texture_version);
+    }
+
+    /// Copies the `[x, y, width, height]` rectangle `src_rect` of `src` into texture `id`, with its
+    /// top-left corner at `dst_pos`, e.g. to upload only the changed part of a video frame. Fails
+    /// with [`PainterError::MissingTexture`] if no such texture is allocated.
+    ///
+    /// # Panics
+    /// If either rectangle exceeds the bounds of its image.
+    pub fn update_texture_region(
+        &mut self,
+        id: TextureId,
+        src: &Buffer2d<Rgba>,
+        src_rect: [usize; 4],
+        dst_pos: [usize; 2],
+    ) -> Result<(), PainterError> {
+        self.textures
+            .get_mut(&id)
+            .ok_or(PainterError::MissingTexture { id })?
+            .update_region(src, src_rect, dst_pos);
+        self.texture_changed(id);
+        Ok(())
+    }
+
+    /// Alpha of the pixel at `x`, `y` of a painted buffer, or 0 outside of the buffer. Lets a host
+    /// route input to the GUI only where it drew something.
+    pub fn pixel_alpha_at(buffer: &Buffer2d<u32>, x: usize, y: usize) -> u8 {
+        let [width, height] = buffer.size();
+        if x < width && y < height {
+            buffer.read([x, y]).to_le_bytes()[3]
+        } else {
+            0
+        }
+    }
+
+    /// Whether the pixel at `x`, `y` of a painted buffer is fully opaque.
+    pub fn is_opaque_at(buffer: &Buffer2d<u32>, x: usize, y: usize) -> bool {
+        Self::pixel_alpha_at(buffer, x, y) == u8::MAX
+    }
+
+    /// Applies `textures_delta` and draws `clipped_primitives` into a new buffer of `screen_size`
+    /// pixels.
+    ///
+    /// `pixels_per_point` must be the value the primitives were tessellated with (e.g.
+    /// [`egui::FullOutput::pixels_per_point`]), otherwise the UI is drawn mis-scaled: it maps
+    /// points to pixels for both vertex positions and clip rects.
+    ///
+    /// Primitives are blended strictly in the order given. egui's tessellator emits them sorted by
+    /// layer (background up to tooltips), so that order must be preserved by anything filtering or
+    /// batching them before they get here.
+    pub fn paint_and_update_textures(
+        &mut self,
+        textures_delta: &TexturesDelta,
+        clipped_primitives: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        screen_size: [usize; 2],
+    ) -> euc::Buffer2d<u32> {
+        self.paint_iter(
+            textures_delta,
+            clipped_primitives,
+            pixels_per_point,
+            screen_size,
+        )
+    }
+
+    /// Like [`Painter::paint_and_update_textures`], but takes primitives from an iterator, so they
+    /// can be filtered or generated lazily.
+    ///
+    /// Primitives are consumed one at a time and drawn immediately, so nothing is gained from
+    /// looking ahead: the iterator just has to yield them in the order egui emitted them.
+    pub fn paint_iter<'a>(
+        &mut self,
+        textures_delta: &TexturesDelta,
+        clipped_primitives: impl IntoIterator<Item = &'a ClippedPrimitive>,
+        pixels_per_point: f32,
+        screen_size: [usize; 2],
+    ) -> Buffer2d<u32> {
+        self.begin_frame(textures_delta);
+
+        let mut image = Buffer2d::fill(screen_size, 0);
+        self.paint(&mut image, clipped_primitives, pixels_per_point);
+
+        self.end_frame();
+
+        image
+    }
+
+    /// Like [`Painter::paint_and_update_textures`], but hands the finished frame to `present`
+    /// instead of returning it, e.g. to copy it out by DMA, and returns what `present` does. The
+    /// buffer is kept by the painter and reused for the next frame of the same size, so steady
+    /// state painting allocates nothing.
+    pub fn paint_with<R>(
+        &mut self,
+        textures_delta: &TexturesDelta,
+        clipped_primitives: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        screen_size: [usize; 2],
+        present: impl FnOnce(&Buffer2d<u32>) -> R,
+    ) -> R {
+        let mut image = self.scratch.take_color(screen_size);
+        diff::clear_region(&mut image, [0, 0, screen_size[0], screen_size[1]]);
+
+        self.begin_frame(textures_delta);
+        self.paint(&mut image, clipped_primitives, pixels_per_point);
+        self.end_frame();
+
+        let presented = present(&image);
+        self.scratch.color = Some(image);
+        presented
+    }
+
+    /// Rasterizes `clipped_primitives` into a new transparent layer of `screen_size` pixels, with the
+    /// textures uploaded so far, e.g. to draw static content once and reuse it. Draw the dynamic
+    /// content over the layer each frame with [`Painter::paint_over_background`], or stack
+    /// several layers with [`composite`].
+    pub fn rasterize_to_layer<'a>(
+        &mut self,
+        clipped_primitives: impl IntoIterator<Item = &'a ClippedPrimitive>,
+        pixels_per_point: f32,
+        screen_size: [usize; 2],
+    ) -> Buffer2d<u32> {
+        let mut layer = Buffer2d::fill(screen_size, 0);
+        self.paint(&mut layer, clipped_primitives, pixels_per_point);
+        layer
+    }
+
+    /// Like [`Painter::paint_and_update_textures`], but draws over a copy of `background` (e.g. a
+    /// wallpaper) instead of transparency. The output has the size of `background`.
+    pub fn paint_over_background(
+        &mut self,
+        background: &Buffer2d<u32>,
+        textures_delta: &TexturesDelta,
+        clipped_primitives: &[ClippedPrimitive],
+        pixels_per_point: f32,
+    ) -> Buffer2d<u32> {
+        let [width, height] = background.size();
+        let mut image = Buffer2d::fill([width, height], 0);
+        for y in 0..height {
+            for x in 0..width {
+                image.write(x, y, background.read([x, y]));
+            }
+        }
+
+        self.begin_frame(textures_delta);
+        self.paint(&mut image, clipped_primitives, pixels_per_point);
+        self.end_frame();
+
+        image
+    }
+
+    /// Starts a frame made of any number of [`Painter::paint`] calls, up to [`Painter::end_frame`].
+    /// Applies the texture uploads of `textures_delta`, and resets per-frame state such as
+    /// [`Painter::drew_visible_pixels`] and [`Painter::last_errors`]. The textures it frees are kept
+    /// until the frame ends.
+    pub fn begin_frame(&mut self, textures_delta: &TexturesDelta) {
+        self.errors.clear();
+        self.allocate_textures(textures_delta);
+        self.pending_frees.clone_from(&textures_delta.free);
+        self.drew_visible_pixels = false;
+        self.stats = PaintStats::default();
+
+        #[cfg(feature = "draw-records")]
+        self.draw_records.clear();
+    }
+
+    /// Draws `clipped_primitives` over the existing contents of `target`, as part of the frame
+    /// started by [`Painter::begin_frame`].
+    pub fn paint<'a>(
+        &mut self,
+        target: &mut Buffer2d<u32>,
+        clipped_primitives: impl IntoIterator<Item = &'a ClippedPrimitive>,
+        pixels_per_point: f32,
+    ) {
+        let [width, height] = target.size();
+        self.render_into(
+            target,
+            clipped_primitives,
+            pixels_per_point,
+            [0, 0, width, height],
+        );
+    }
+
+    /// Like [`Painter::paint`], but for any [`RenderTarget`]. The rasterizer needs a [`Buffer2d`],
+    /// which this painter keeps around between calls, so the pixels the primitives can touch are
+    /// copied into one and back. That is the bounding box of the primitives within the viewport,
+    /// or the whole target while a [`PostProcess`] is set, since it applies everywhere.
+    pub fn paint_to<'a>(
+        &mut self,
+        target: &mut impl RenderTarget,
+        clipped_primitives: impl IntoIterator<Item = &'a ClippedPrimitive>,
+        pixels_per_point: f32,
+    ) {
+        let screen_size = [target.width(), target.height()];
+        let [width, height] = screen_size;
+        let mut primitives: Vec<&ClippedPrimitive> =
+            scratch::recycle(std::mem::take(&mut self.scratch.staged));
+        primitives.extend(clipped_primitives);
+
+        let region = if self.post_process.is_some() {
+            [0, 0, width, height]
+        } else {
+            let pixel_scale = self.pixel_scale(pixels_per_point);
+            let to_target = self.to_target(screen_size, pixel_scale);
+            let bounds = primitives
+                .iter()
+                .map(|item| diff::primitive_bounds(item, pixel_scale, to_target, screen_size))
+                .fold(None, diff::union)
+                .unwrap_or_default();
+            self.viewport
+                .map_or(bounds, |viewport| occlusion::intersect(bounds, viewport))
+        };
+        let [min_x, min_y, region_width, region_height] = region;
+        let (xs, ys) = (min_x..min_x + region_width, min_y..min_y + region_height);
+
+        let mut color = self.scratch.take_color(screen_size);
+        for y in ys.clone() {
+            for x in xs.clone() {
+                color.write(x, y, target.get_pixel(x, y));
+            }
+        }
+
+        self.render_into(
+            &mut color,
+            primitives.iter().copied(),
+            pixels_per_point,
+            region,
+        );
+
+        for y in ys {
+            for x in xs.clone() {
+                target.set_pixel(x, y, color.read([x, y]));
+            }
+        }
+        self.scratch.color = Some(color);
+        self.scratch.staged = scratch::recycle(primitives);
+    }
+
+    /// Like [`Painter::paint`], but draws into the pixels of texture `id`, for cheap render to
+    /// texture: later meshes sampling `id` see the result. `pixels_per_point` maps points to pixels
+    /// of the texture at its full size, even if it is stored downscaled. Meshes sampling `id`
+    /// themselves see its contents from before this call. Pixels go through the 8-bit output
+    /// format on the way, so HDR values of user textures are clamped. Fails with
+    /// [`PainterError::MissingTexture`], drawing nothing, if no such texture is allocated.
+    pub fn paint_into_texture<'a>(
+        &mut self,
+        id: TextureId,
+        clipped_primitives: impl IntoIterator<Item = &'a ClippedPrimitive>,
+        pixels_per_point: f32,
+    ) -> Result<(), PainterError> {
+        let texture = self
+            .textures
+            .get(&id)
+            .ok_or(PainterError::MissingTexture { id })?;
+        let [width, height] = texture.pixels.size();
+        let scale = width as f32 / texture.size[0].max(1) as f32;
+
+        let mut color = self.scratch.take_color([width, height]);
+        for y in 0..height {
+            for x in 0..width {
+                color.write(x, y, pack_pixel(texture.pixels.read([x, y])));
+            }
+        }
+
+        self.paint(&mut color, clipped_primitives, pixels_per_point * scale);
+
+        let texture = self
+            .textures
+            .get_mut(&id)
+            .expect("Texture freed while painting into it");
+        for y in 0..height {
+            for x in 0..width {
+                texture
+                    .pixels
+                    .write_rgba(x, y, unpack_pixel(color.read([x, y])));
+            }
+        }
+        self.scratch.color = Some(color);
+        self.texture_changed(id);
+        Ok(())
+    }
+
+    /// Ends the frame started by [`Painter::begin_frame`], freeing the textures it was asked to.
+    pub fn end_frame(&mut self) {
+        // Deferred uploads of freed textures would arrive at nothing
+        self.pending_uploads
+            .retain(|(id, _)| !self.pending_frees.contains(id));
+        for id in self.pending_frees.drain(..) {
+            self.textures.remove(&id);
+            self.texture_versions.remove(&id);
+        }
+    }
+
+    /// Like [`Painter::paint_and_update_textures`], additionally returning the coverage (accumulated
+    /// alpha) of every pixel as its own buffer, for compositing onto arbitrary backgrounds.
+    pub fn paint_color_and_coverage(
+        &mut self,
+        textures_delta: &TexturesDelta,
+        clipped_primitives: &[ClippedPrimitive],
+        pixels_per_point: f32,
+        screen_size: [usize; 2],
+    ) -> (Buffer2d<u32>, Buffer2d<u8>) {
+        let color = self.paint_and_update_textures(
+            textures_delta,
+            clipped_primitives,
             pixels_per_point,
             screen_size,
         );
+
+        let [width, height] = screen_size;
+        let mut coverage = Buffer2d::fill(screen_size, 0);
+        for y in 0..height {
+            for x in 0..width {
+                coverage.write(x, y, color.read([x, y]).to_le_bytes()[3]);
+            }
+        }
+
+        (color, coverage)
+    }
+
+    /// Tessellates and draws `shapes` directly, without running an [`egui::Context`] frame, e.g. for
+    /// plots built from hand-made shapes. Shapes are clipped to the screen.
+    ///
+    /// Text shapes must be laid out with `fonts`. Any pending change to the font atlas is uploaded
+    /// first, so the first call for a given `fonts` must be made with this painter.
+    pub fn paint_shapes(
+        &mut self,
+        shapes: Vec<egui::Shape>,
+        fonts: &epaint::Fonts,
+        tessellation_options: epaint::TessellationOptions,
+        pixels_per_point: f32,
+        screen_size: [usize; 2],
+    ) -> Buffer2d<u32> {
+        let mut textures_delta = TexturesDelta::default();
+        if let Some(font_delta) = fonts.font_image_delta() {
+            textures_delta.set.push((TextureId::default(), font_delta));
+        }
+
+        let screen_rect = egui::Rect::from_min_size(
+            egui::Pos2::ZERO,
+            egui::vec2(screen_size[0] as f32, screen_size[1] as f32) / pixels_per_point,
+        );
+        let shapes = shapes
+            .into_iter()
+            .map(|shape| epaint::ClippedShape {
+                clip_rect: screen_rect,
+                shape,
+            })
+            .collect();
+
+        let (font_tex_size, prepared_discs) = {
+            let atlas = fonts.texture_atlas();
+            let atlas = atlas.lock();
+            (atlas.size(), atlas.prepared_discs())
+        };
+        let clipped_primitives = epaint::Tessellator::new(
+            pixels_per_point,
+            tessellation_options,
+            font_tex_size,
+            prepared_discs,
+        )
+        .tessellate_shapes(shapes);
+
+        self.paint_and_update_textures(
+            &textures_delta,
+            &clipped_primitives,
+            pixels_per_point,
+            screen_size,
+        )
+    }
+
+    fn allocate_textures(&mut self, textures_delta: &TexturesDelta) {
+        if self.upload_budget.is_some() || !self.pending_uploads.is_empty() {
+            let mut budget = self.upload_budget.unwrap_or(usize::MAX);
+            let mut uploaded_any = self.apply_pending_uploads(&mut budget, false);
+            for (id, delta) in &textures_delta.set {
+                let bytes = upload_bytes(delta);
+                if self.pending_uploads.is_empty() && bytes <= budget {
+                    budget -= bytes;
+                    self.apply_texture_delta(*id, delta);
+                    uploaded_any = true;
+                } else {
+                    // Later deltas may depend on deferred ones, so they queue up behind them.
+                    // Only the handle to the image is cloned, not its pixels.
+                    self.pending_uploads.push_back((*id, delta.clone()));
+                }
+            }
+            // Splits the first delta which didn't fit, if there is budget left for it
+            self.apply_pending_uploads(&mut budget, uploaded_any);
+            return;
+        }
+
+        // Both are in delta order, so the created textures are walked in step with the deltas
+        let mut created = self.create_textures(textures_delta).into_iter().peekable();
+        self.textures.reserve(created.len());
+        for (i, (id, delta)) in textures_delta.set.iter().enumerate() {
+            match created.next_if(|(index, _)| *index == i) {
+                Some((_, Ok(texture))) => {
+                    self.textures.insert(*id, texture);
+                    self.texture_changed(*id);
+                }
+                Some((_, Err(texture::EmptyImage))) => {
+                    self.errors.push(PainterError::EmptyImage { id: *id });
+                }
+                None => self.apply_texture_delta(*id, delta),
+            }
+        }
+    }
+
+    /// How a whole upload to texture `id` is stored: its storage, the size limit of its pixels
+    /// and whether it holds linear color.
+    fn texture_settings(&self, id: TextureId) -> (TextureStorage, usize, bool) {
+        let storage = self.texture_storages.get(&id).copied();
+        (
+            storage.unwrap_or(self.texture_storage),
+            self.max_texture_size,
+            self.linear_textures.contains(&id),
+        )
+    }
+
+    fn apply_texture_delta(&mut self, id: TextureId, delta: &epaint::ImageDelta) {
+        let (storage, max_size, linear) = self.texture_settings(id);
+        let whole = delta.is_whole();
+        let create =
+            || SoftwareTexture::new(&delta.image, delta.options, storage, max_size, linear);
+        let result = match self.textures.get_mut(&id) {
+            // Settings changed since the texture was created apply from its next whole upload
+            Some(texture) if whole && !texture.is_stored_as(storage, max_size, linear) => create()
+                .map(|new| {
+                    let border = texture.border;
+                    *texture = new;
+                    texture.border = border;
+                }),
+            Some(texture) => texture.update(delta),
+            None if whole => create().map(|texture| {
+                self.textures.insert(id, texture);
+            }),
+            None => {
+                self.errors.push(PainterError::MissingTexture { id });
+                Ok(())
+            }
+        };
+        if let Err(texture::EmptyImage) = result {
+            self.errors.push(PainterError::EmptyImage { id });
+        }
+        if self.textures.contains_key(&id) {
+            self.texture_changed(id);
+        }
+    }
+
+    /// Applies deferred texture deltas in order, taking the bytes they use from `budget`, see
+    /// [`Painter::set_upload_budget`]. The delta which doesn't fit is split, and its first rows
+    /// which do are applied; at least one row is if nothing was `uploaded_any` yet this frame.
+    /// Returns whether anything was uploaded by now.
+    fn apply_pending_uploads(&mut self, budget: &mut usize, mut uploaded_any: bool) -> bool {
+        while let Some((id, delta)) = self.pending_uploads.pop_front() {
+            let epaint::ImageData::Color(patch) = &delta.image;
+            let row_bytes = patch.width() * std::mem::size_of::<egui::Color32>();
+            let bytes = upload_bytes(&delta);
+            if bytes <= *budget {
+                *budget -= bytes;
+                self.apply_texture_delta(id, &delta);
+                uploaded_any = true;
+                continue;
+            }
+
+            let rows = *budget / row_bytes;
+            // Every frame makes progress, however small the budget
+            let rows = if uploaded_any { rows } else { rows.max(1) };
+            if rows == 0 {
+                self.pending_uploads.push_front((id, delta));
+                break;
+            }
+            *budget = budget.saturating_sub(rows * row_bytes);
+            uploaded_any = true;
+            if rows >= patch.height() {
+                // A single row over budget goes in whole, rather than leaving an empty tail
+                self.apply_texture_delta(id, &delta);
+                break;
+            }
+
+            if delta.is_whole() {
+                // The rows arrive into a transparent texture of the final size
+                let (storage, max_size, linear) = self.texture_settings(id);
+                let old = self.textures.get(&id);
+                if old.is_none_or(|old| {
+                    old.size != patch.size || !old.is_stored_as(storage, max_size, linear)
+                }) {
+                    let border = old.and_then(|old| old.border);
+                    let mut texture = SoftwareTexture::blank(
+                        patch.size,
+                        delta.options,
+                        storage,
+                        max_size,
+                        linear,
+                    );
+                    texture.border = border;
+                    self.textures.insert(id, texture);
+                }
+            }
+            let (head, tail) = texture::split_delta_rows(&delta, rows);
+            self.apply_texture_delta(id, &head);
+            self.pending_uploads.push_front((id, tail));
+            break;
+        }
+        uploaded_any
+    }
+
+    /// Creates the textures which `textures_delta` sets for the first time, returning each with the
+    /// index of the delta it was created from, in delta order. Decoding each is independent of the
+    /// others, so with the `par` feature they are decoded in parallel, e.g. the font atlas and
+    /// images of the first frame, on at most one thread per core. Small batches are decoded in
+    /// place, as threads would cost more.
+    fn create_textures(
+        &self,
+        textures_delta: &TexturesDelta,
+    ) -> Vec<(usize, Result<SoftwareTexture, texture::EmptyImage>)> {
+        // Only the first delta of each id can create it, later ones update it in order
+        let mut seen = HashSet::new();
+        let indices: Vec<usize> = (0..textures_delta.set.len())
+            .filter(|&i| {
+                let (id, delta) = &textures_delta.set[i];
+                seen.insert(*id) && delta.is_whole() && !self.textures.contains_key(id)
+            })
+            .collect();
+
+        let (storage, max_size) = (self.texture_storage, self.max_texture_size);
+        let linear_textures = &self.linear_textures;
+        let texture_storages = &self.texture_storages;
+        let create = |&i: &usize| {
+            let (id, delta) = &textures_delta.set[i];
+            let linear = linear_textures.contains(id);
+            let storage = texture_storages.get(id).copied().unwrap_or(storage);
+            let texture =
+                SoftwareTexture::new(&delta.image, delta.options, storage, max_size, linear);
+            (i, texture)
+        };
+
+        // Below this many texels in total, spawning threads costs more than decoding takes
+        #[cfg(feature = "par")]
+        const PARALLEL_TEXELS: usize = 128 * 128;
+        #[cfg(feature = "par")]
+        let texels: usize = indices
+            .iter()
+            .map(|&i| {
+                let epaint::ImageData::Color(image) = &textures_delta.set[i].1.image;
+                image.width() * image.height()
+            })
+            .sum();
+
+        #[cfg(feature = "par")]
+        let textures: Vec<_> = if indices.len() > 1 && texels >= PARALLEL_TEXELS {
+            // One chunk of textures per thread, never more threads than cores
+            let threads = std::thread::available_parallelism()
+                .map_or(1, |n| n.get())
+                .min(indices.len());
+            std::thread::scope(|scope| {
+                let handles: Vec<_> = indices
+                    .chunks(indices.len().div_ceil(threads))
+                    .map(|chunk| scope.spawn(move || chunk.iter().map(create).collect::<Vec<_>>()))
+                    .collect();
+                handles
+                    .into_iter()
+                    .flat_map(|handle| {
+                        handle
+                            .join()
+                            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                    })
+                    .collect()
+            })
+        } else {
+            indices.iter().map(create).collect()
+        };
+        #[cfg(not(feature = "par"))]
+        let textures: Vec<_> = indices.iter().map(create).collect();
+
+        textures
+    }
+
+    /// Draws `clipped_primitives` over the existing contents of `color`, only touching pixels inside
+    /// of `region` (`[x, y, width, height]` in pixels).
+    fn render_into<'a>(
+        &mut self,
+        color: &mut Buffer2d<u32>,
+        clipped_primitives: impl IntoIterator<Item = &'a ClippedPrimitive>,
+        pixels_per_point: f32,
+        region: [usize; 4],
+    ) {
+        let screen_size = color.size();
+        self.scratch.begin();
+
+        let pixels_written = AtomicUsize::new(0);
+        let count = self.count_pixels.then_some(&pixels_written);
+        let visible = AtomicBool::new(false);
+
+        if let Some(uv_buffer) = &mut self.uv_buffer {
+            if uv_buffer.size() == screen_size {
+                let [x, y, width, height] = region;
+                for y in y..y + height {
+                    for x in x..x + width {
+                        uv_buffer.write(x, y, debug::NO_UV);
+                    }
+                }
+            } else {
+                *uv_buffer = Buffer2d::fill(screen_size, debug::NO_UV);
+            }
+        }
+
+        let pixel_scale = self.pixel_scale(pixels_per_point);
+        let mut primitives: Vec<&ClippedPrimitive> =
+            scratch::recycle(std::mem::take(&mut self.scratch.primitives));
+        primitives.extend(clipped_primitives);
+        let mut occlusions = std::mem::take(&mut self.scratch.occlusions);
+        // Skipping pixels only gives the same result if what is drawn over them is opaque in the end
+        let cull = self.occlusion_culling
+            && self.opacity == 1.0
+            && self.fragment_hook.is_none()
+            // Feathered edges of opaque rectangles aren't opaque
+            && self.clip_feather <= 0.0
+            && matches!(self.blend_op, BlendOp::Over | BlendOp::Replace)
+            // Rotated rectangles aren't axis-aligned anymore
+            && self.transform == Affine2::IDENTITY;
+        let to_target = self.to_target(screen_size, pixel_scale);
+        let occluded: &[occlusion::Occlusion] = if cull {
+            occlusion::find_occlusions(
+                &primitives,
+                &self.textures,
+                self.texture_id_remap.as_deref(),
+                pixel_scale,
+                to_target,
+                screen_size,
+                self.viewport
+                    .map_or(region, |viewport| occlusion::intersect(region, viewport)),
+                &mut occlusions,
+                &mut self.scratch.occluders,
+            );
+            &occlusions[..primitives.len()]
+        } else {
+            &[]
+        };
+
+        for (i, &item) in primitives.iter().enumerate() {
+            if let epaint::Primitive::Mesh(mesh) = &item.primitive {
+                let occlusion = occluded.get(i);
+                if occlusion.is_some_and(|occlusion| occlusion.hidden) {
+                    self.stats.occluded_meshes += 1;
+                    continue;
+                }
+
+                let clip = Scissor::from_clip_rect(
+                    (),
+                    screen_size,
+                    pixel_scale,
+                    to_target.apply_rect(item.clip_rect),
+                );
+                // Fades towards the edges of the clip rect only, not those of the painted region
+                let feathered = FeatheredScissor::new(
+                    Occluded {
+                        inner: CountWrites {
+                            inner: &mut *color,
+                            count,
+                            visible: &visible,
+                        },
+                        rects: occlusion.map_or(&[][..], |occlusion| &occlusion.rects),
+                    },
+                    [clip.x, clip.y, clip.width, clip.height],
+                    self.clip_feather,
+                );
+                let mut scissor = Scissor::new(feathered, clip.x, clip.y, clip.width, clip.height)
+                    .restrict(region)
+                    .restrict(self.viewport.unwrap_or(region));
+
+                if scissor.width == 0 || scissor.height == 0 {
+                    continue;
+                }
+
+                if let Err(error) = validate_mesh(mesh) {
+                    self.stats.invalid_meshes += 1;
+                    self.errors.push(PainterError::InvalidMesh {
+                        primitive: i,
+                        error,
+                    });
+                    continue;
+                }
+
+                if self.debug_mode == DebugMode::ClipRects {
+                    self.scratch.clip_rects.push([
+                        scissor.x,
+                        scissor.y,
+                        scissor.width,
+                        scissor.height,
+                    ]);
+                }
+
+                let to_pixels = |index: u32| {
+                    let pos = to_target.apply(mesh.vertices[index as usize].pos);
+                    (pos.to_vec2() * pixel_scale).to_pos2()
+                };
+                let indices = non_degenerate_indices(
+                    &mesh.indices,
+                    to_pixels,
+                    self.degenerate_triangle_area,
+                    &mut self.scratch.indices,
+                );
+                self.stats.degenerate_triangles += (mesh.indices.len() - indices.len()) / 3;
+
+                if self.debug_mode == DebugMode::Wireframe {
+                    let rect = [scissor.x, scissor.y, scissor.width, scissor.height];
+                    self.scratch.wireframe.extend(
+                        indices
+                            .chunks_exact(3)
+                            .map(|tri| (rect, [tri[0], tri[1], tri[2]].map(to_pixels))),
+                    );
+                }
+
+                let texture_id =
+                    remap_texture_id(self.texture_id_remap.as_deref(), mesh.texture_id);
+                let Some(texture) = self.textures.get(&texture_id) else {
+                    let error = PainterError::MissingTexture { id: texture_id };
+                    if !self.errors.contains(&error) {
+                        self.errors.push(error);
+                    }
+                    continue;
+                };
+
+                self.stats.meshes += 1;
+                self.stats.triangles += indices.len() / 3;
+
+                // Minifying along either axis calls for the minification filter
+                let filter = texture_filter(mesh, texture, pixel_scale.min_elem(), self.lod_bias);
+
+                #[cfg(feature = "draw-records")]
+                self.draw_records.push(DrawRecord {
+                    texture_id,
+                    clip_rect: item.clip_rect,
+                    triangle_count: indices.len() / 3,
+                    filter,
+                    wrap_mode: texture.options.wrap_mode,
+                });
+
+                let screen_size_points =
+                    egui::Vec2::new(screen_size[0] as f32, screen_size[1] as f32) / pixel_scale;
+
+                let coverage_gamma = if self.font_textures.contains(&texture_id) {
+                    self.text_gamma
+                } else {
+                    1.0
+                };
+
+                let srgb = self.srgb_filtering;
+                let draw = MeshDraw {
+                    mesh,
+                    indices,
+                    screen_size_points,
+                    coverage_gamma,
+                    opacity: self.opacity,
+                    fragment_hook: self.fragment_hook.as_deref(),
+                    blend_op: self.blend_op,
+                    dither: self.dither,
+                    transform: to_target,
+                    border: texture.border,
+                    target: &mut scissor,
+                };
+
+                match &texture.pixels {
+                    TexturePixels::Rgba(pixels) => {
+                        render_mesh(pixels, filter, texture.options.wrap_mode, srgb, draw)
+                    }
+                    TexturePixels::Packed(pixels) => {
+                        render_mesh(pixels, filter, texture.options.wrap_mode, srgb, draw)
+                    }
+                    TexturePixels::Rgba4444(pixels) => {
+                        render_mesh(pixels, filter, texture.options.wrap_mode, srgb, draw)
+                    }
+                    TexturePixels::Rgb565A8(pixels) => {
+                        render_mesh(pixels, filter, texture.options.wrap_mode, srgb, draw)
+                    }
+                    TexturePixels::Alpha8(pixels) => {
+                        render_mesh(pixels, filter, texture.options.wrap_mode, srgb, draw)
+                    }
+                }
+
+                if let Some(uv_buffer) = &mut self.uv_buffer {
+                    let (x, y, width, height) =
+                        (scissor.x, scissor.y, scissor.width, scissor.height);
+                    debug::UvPipeline {
+                        vertices: &mesh.vertices,
+                        screen_size_points,
+                        transform: to_target,
+                    }
+                    .render(
+                        indices,
+                        &mut Scissor::new(uv_buffer, x, y, width, height),
+                        &mut Empty::<f32>::default(),
+                    );
+                }
+            }
+        }
+
+        self.scratch.primitives = scratch::recycle(primitives);
+        self.scratch.occlusions = occlusions;
+        self.stats.pixels_written += pixels_written.into_inner();
+
+        if let Some(post_process) = self.post_process {
+            let region = self
+                .viewport
+                .map_or(region, |viewport| occlusion::intersect(region, viewport));
+            postprocess::apply_region(color, post_process, region);
+        }
+
+        for &(rect, [a, b, c]) in &self.scratch.wireframe {
+            for (p0, p1) in [(a, b), (b, c), (c, a)] {
+                draw_line_aa_within(color, rect, p0, p1, 1.0, debug::WIREFRAME_COLOR);
+            }
+        }
+
+        for &rect in &self.scratch.clip_rects {
+            debug::outline_rect(color, rect, debug::CLIP_RECT_COLOR);
+        }
+
+        self.drew_visible_pixels |= visible.into_inner()
+            || !self.scratch.wireframe.is_empty()
+            || !self.scratch.clip_rects.is_empty();
+    }
+}
+
+/// `indices` without the triangles of at most `max_area` square pixels, with vertex positions in
+/// pixels given by `to_pixels`. Interpolating across such triangles divides by (almost) zero, and
+/// they cover nothing anyway. Only copied into `scratch` if any triangle is dropped.
+fn non_degenerate_indices<'a>(
+    indices: &'a [u32],
+    to_pixels: impl Fn(u32) -> egui::Pos2,
+    max_area: f32,
+    scratch: &'a mut Vec<u32>,
+) -> &'a [u32] {
+    let keep = |tri: &[u32]| {
+        let [a, b, c] = [tri[0], tri[1], tri[2]].map(&to_pixels);
+        let (ab, ac) = (b - a, c - a);
+        let area = 0.5 * (ab.x * ac.y - ab.y * ac.x).abs();
+        // NaN areas are dropped too
+        area > max_area
+    };
+    if indices.chunks_exact(3).all(&keep) {
+        return indices;
+    }
+    scratch.clear();
+    scratch.extend(indices.chunks_exact(3).filter(|tri| keep(tri)).flatten());
+    scratch
+}
+
+/// Everything needed to draw a mesh, except for the texture sampler.
+struct MeshDraw<'a, 'b> {
+    mesh: &'a epaint::Mesh,
+    /// Indices of `mesh` to draw, see [`non_degenerate_indices`].
+    indices: &'a [u32],
+    screen_size_points: egui::Vec2,
+    coverage_gamma: f32,
+    opacity: f32,
+    fragment_hook: Option<&'a FragmentHook>,
+    blend_op: BlendOp,
+    /// Whether to draw through a [`dither::Dithered`] target.
+    dither: bool,
+    transform: Affine2,
+    border: Option<Rgba>,
+    target: &'a mut Scissor<FeatheredScissor<Occluded<'b, CountWrites<'b, &'b mut Buffer2d<u32>>>>>,
+}
+
+impl MeshDraw<'_, '_> {
+    fn with<S>(self, sampler: S)
+    where
+        S: UvSampler + Send + Sync,
+    {
+        match self.border {
+            Some(border) => self.render(sampler.bordered(border)),
+            None => self.render(sampler),
+        }
+    }
+
+    fn render<S>(self, sampler: S)
+    where
+        S: UvSampler + Send + Sync,
+    {
+        let pipeline = EguiMeshEucPipeline {
+            vertices: &self.mesh.vertices,
+            sampler,
+            screen_size_points: self.screen_size_points,
+            coverage_gamma: self.coverage_gamma,
+            opacity: self.opacity,
+            fragment_hook: self.fragment_hook,
+            blend_op: self.blend_op,
+            transform: self.transform,
+        };
+        // Nothing is depth tested, so no depth buffer is needed
+        let mut depth = Empty::<f32>::default();
+        if self.dither {
+            let mut target = dither::Dithered { inner: self.target };
+            dither::DitheredPipeline(pipeline).render(self.indices, &mut target, &mut depth);
+        } else {
+            pipeline.render(self.indices, self.target, &mut depth);
+        }
+    }
+}
+
+/// How many output pixels `mesh` covers per texel of a `texture_size` texture, averaged over its
+/// triangles. Above `1.0` the texture is magnified, below it is minified. `None` if the mesh has no
+/// area on screen or in the texture, or if it indexes vertices it doesn't have.
+pub fn mesh_texel_scale(
+    mesh: &epaint::Mesh,
+    texture_size: [usize; 2],
+    pixels_per_point: f32,
+) -> Option<f32> {
+    // Twice the area of a triangle, i.e. the magnitude of the cross product of two of its edges
+    let doubled_area = |[a, b, c]: [egui::Vec2; 3]| {
+        let (ab, ac) = (b - a, c - a);
+        (ab.x * ac.y - ab.y * ac.x).abs()
+    };
+
+    let mut screen_area = 0.0;
+    let mut uv_area = 0.0;
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [a, b, c] = [0, 1, 2].map(|i| mesh.vertices.get(triangle[i] as usize));
+        let vertices = [*a?, *b?, *c?];
+        screen_area += doubled_area(vertices.map(|v| v.pos.to_vec2()));
+        uv_area += doubled_area(vertices.map(|v| v.uv.to_vec2()));
+    }
+
+    let [width, height] = texture_size;
+    let screen_area = screen_area * pixels_per_point * pixels_per_point;
+    let texel_area = uv_area * width as f32 * height as f32;
+    (screen_area > 0.0 && texel_area > 0.0).then(|| (screen_area / texel_area).sqrt())
+}
+
+/// The filter of `texture` to draw `mesh` with: its minification filter where the mesh shrinks
+/// the texture, and its magnification filter otherwise. The shrinking is measured as a level of
+/// detail, `log2(texels per pixel) + lod_bias`, minifying above zero.
+fn texture_filter(
+    mesh: &epaint::Mesh,
+    texture: &SoftwareTexture,
+    pixels_per_point: f32,
+    lod_bias: f32,
+) -> TextureFilter {
+    let options = texture.options;
+    if options.minification == options.magnification {
+        return options.magnification;
+    }
+    match mesh_texel_scale(mesh, texture.pixels.size(), pixels_per_point) {
+        Some(scale) if -scale.log2() + lod_bias > 0.0 => options.minification,
+        _ => options.magnification,
+    }
+}
+
+/// Bytes of `delta` counted against [`Painter::set_upload_budget`].
+fn upload_bytes(delta: &epaint::ImageDelta) -> usize {
+    let epaint::ImageData::Color(patch) = &delta.image;
+    patch.width() * patch.height() * std::mem::size_of::<egui::Color32>()
+}
+
+/// Draws a mesh, sampling `pixels` with `filter` and `wrap_mode`.
+fn render_mesh<T>(
+    pixels: &T,
+    filter: TextureFilter,
+    wrap_mode: TextureWrapMode,
+    srgb: bool,
+    draw: MeshDraw<'_, '_>,
+) where
+    T: Texture<2, Index = usize, Texel = Rgba> + Sync,
+{
+    match (filter, wrap_mode) {
+        (TextureFilter::Linear, wrap_mode) if srgb => {
+            draw.with(SrgbBilinear::new(pixels, wrap_mode))
+        }
+        (TextureFilter::Linear, wrap_mode) => draw.with(Bilinear::new(pixels, wrap_mode)),
+        #[cfg(feature = "fixed-point")]
+        (TextureFilter::Nearest, wrap_mode) => draw.with(fixed::Nearest::new(pixels, wrap_mode)),
+        #[cfg(not(feature = "fixed-point"))]
+        (TextureFilter::Nearest, TextureWrapMode::Repeat) => draw.with(pixels.nearest().tiled()),
+        #[cfg(not(feature = "fixed-point"))]
+        (TextureFilter::Nearest, TextureWrapMode::ClampToEdge) => {
+            draw.with(pixels.nearest().clamped())
+        }
+        #[cfg(not(feature = "fixed-point"))]
+        (TextureFilter::Nearest, TextureWrapMode::MirroredRepeat) => {
+            draw.with(pixels.nearest().mirrored())
+        }
+    }
+}
+
+pub fn euc_to_egui_colorimage(euc: euc::Buffer2d<u32>) -> egui::ColorImage {
+    let pixels = euc.raw().iter().map(|px| {
+        let [r, g, b, a] = px.to_le_bytes();
+        egui::Color32::from_rgba_unmultiplied(r, g, b, a)
+    })
+    .collect();
+    egui::ColorImage::new(euc.size(), pixels)
+}
+
+/// Copies `buffer` into RGBA8 bytes with every row padded to a multiple of `row_align` bytes, as
+/// display controllers fed by DMA often require. Returns the bytes and the stride of each row in
+/// bytes. Padding bytes are zero.
+pub fn buffer_to_aligned_bytes(buffer: &Buffer2d<u32>, row_align: usize) -> (Vec<u8>, usize) {
+    let [width, height] = buffer.size();
+    let stride = (width * 4).next_multiple_of(row_align.max(1));
+
+    let mut bytes = vec![0; stride * height];
+    if width > 0 {
+        for (row, pixels) in bytes
+            .chunks_exact_mut(stride)
+            .zip(buffer.raw().chunks_exact(width))
+        {
+            for (dst, pixel) in row.chunks_exact_mut(4).zip(pixels) {
+                dst.copy_from_slice(&pixel.to_le_bytes());
+            }
+        }
+    }
+    (bytes, stride)
+}
+
+/// The smallest `[x, y, width, height]` rectangle containing every pixel of `buffer` with non-zero
+/// alpha, e.g. to crop a screenshot to its content, or `None` if nothing was drawn.
+pub fn content_bounds(buffer: &Buffer2d<u32>) -> Option<[usize; 4]> {
+    let [width, _] = buffer.size();
+    let mut bounds: Option<[usize; 4]> = None;
+    for (i, pixel) in buffer.raw().iter().enumerate() {
+        if pixel.to_le_bytes()[3] == 0 {
+            continue;
+        }
+        let (x, y) = (i % width, i / width);
+        let [min_x, min_y, max_x, max_y] = bounds.get_or_insert([x, y, x, y]);
+        *min_x = (*min_x).min(x);
+        *min_y = (*min_y).min(y);
+        *max_x = (*max_x).max(x);
+        *max_y = (*max_y).max(y);
+    }
+    bounds.map(|[min_x, min_y, max_x, max_y]| [min_x, min_y, max_x - min_x + 1, max_y - min_y + 1])
+}
+
+/// Splits `buffer` into a plane of packed RGB bytes and a plane of alpha bytes, both row-major
+/// without padding, for consumers wanting planar data such as YUVA encoders. Output pixels already
+/// hold straight alpha, so the color plane is as painted, not premultiplied.
+pub fn buffer_to_planar(buffer: &Buffer2d<u32>) -> (Vec<u8>, Vec<u8>) {
+    let mut rgb = Vec::with_capacity(buffer.raw().len() * 3);
+    let mut alpha = Vec::with_capacity(buffer.raw().len());
+    for pixel in buffer.raw() {
+        let [r, g, b, a] = pixel.to_le_bytes();
+        rgb.extend([r, g, b]);
+        alpha.push(a);
+    }
+    (rgb, alpha)
+}
+
+/// Copies `buffer` into packed 24-bit RGB bytes, row-major without padding, for framebuffers
+/// without alpha. Each pixel is composited over `background` (the color the GUI is shown on),
+/// dropping its alpha. See [`Rgb888Target`] to paint into such bytes directly.
+pub fn buffer_to_rgb888(buffer: &Buffer2d<u32>, background: egui::Color32) -> Vec<u8> {
+    buffer
+        .raw()
+        .iter()
+        .flat_map(|&pixel| target::composite_rgb(pixel, background))
+        .collect()
+}
+
+/// Combines each pixel of `src` into `dst`, with the top-left of `src` placed at `origin`.
+/// Pixels falling outside of `dst` are skipped.
+fn blit_with(
+    dst: &mut Buffer2d<u32>,
+    src: &Buffer2d<u32>,
+    [origin_x, origin_y]: [i32; 2],
+    mut combine: impl FnMut(u32, u32) -> u32,
+) {
+    let [dst_width, dst_height] = dst.size();
+    let [src_width, src_height] = src.size();
+
+    // Overlap of the two buffers, in destination coordinates:
+    let min_x = origin_x.clamp(0, dst_width as i32);
+    let min_y = origin_y.clamp(0, dst_height as i32);
+    let max_x = (origin_x + src_width as i32).clamp(min_x, dst_width as i32);
+    let max_y = (origin_y + src_height as i32).clamp(min_y, dst_height as i32);
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let src_pos = [(x - origin_x) as usize, (y - origin_y) as usize];
+            let (x, y) = (x as usize, y as usize);
+            let pixel = combine(dst.read([x, y]), src.read(src_pos));
+            dst.write(x, y, pixel);
+        }
+    }
+}
+
+/// Draws `sprite` (e.g. a cursor) over `dst`, such that the `hotspot` pixel of the sprite lands on `pos`.
+/// The sprite is clipped to the bounds of `dst`.
+pub fn draw_sprite_over(
+    dst: &mut Buffer2d<u32>,
+    sprite: &Buffer2d<u32>,
+    pos: [i32; 2],
+    hotspot: [i32; 2],
+) {
+    let origin = [pos[0] - hotspot[0], pos[1] - hotspot[1]];
+    blit_with(dst, sprite, origin, |screen, sprite| {
+        pack_pixel(over(unpack_pixel(screen), unpack_pixel(sprite)))
+    });
+}
+
+/// Draws an anti-aliased line segment of `width` pixels from `p0` to `p1` over `dst`, with flat
+/// ends at the endpoints. Positions are in pixels, and each pixel's coverage is estimated from the
+/// distance of its center to the segment, smoothing edges over about one pixel.
+pub fn draw_line_aa(
+    dst: &mut Buffer2d<u32>,
+    p0: egui::Pos2,
+    p1: egui::Pos2,
+    width: f32,
+    color: egui::Color32,
+) {
+    let [dst_width, dst_height] = dst.size();
+    draw_line_aa_within(dst, [0, 0, dst_width, dst_height], p0, p1, width, color);
+}
+
+/// [`draw_line_aa`], only touching pixels inside of `[x, y, width, height]` of `dst`.
+fn draw_line_aa_within(
+    dst: &mut Buffer2d<u32>,
+    [clip_x, clip_y, clip_width, clip_height]: [usize; 4],
+    p0: egui::Pos2,
+    p1: egui::Pos2,
+    width: f32,
+    color: egui::Color32,
+) {
+    let color = Rgba::from(color);
+    let half_width = width * 0.5;
+
+    let direction = p1 - p0;
+    let length = direction.length();
+    let along = if length > 0.0 {
+        direction / length
+    } else {
+        egui::Vec2::X
+    };
+
+    // Pixels which can have any coverage:
+    let bounds = egui::Rect::from_two_pos(p0, p1).expand(half_width + 1.0);
+    let (clip_max_x, clip_max_y) = (clip_x + clip_width, clip_y + clip_height);
+    let min_x = (bounds.min.x.floor().max(0.0) as usize).clamp(clip_x, clip_max_x);
+    let min_y = (bounds.min.y.floor().max(0.0) as usize).clamp(clip_y, clip_max_y);
+    let max_x = (bounds.max.x.ceil().max(0.0) as usize).clamp(min_x, clip_max_x);
+    let max_y = (bounds.max.y.ceil().max(0.0) as usize).clamp(min_y, clip_max_y);
+
+    for y in min_y..max_y {
+        for x in min_x..max_x {
+            let offset = egui::Pos2::new(x as f32 + 0.5, y as f32 + 0.5) - p0;
+            let t = offset.dot(along);
+            // Distance beyond the flat ends along the line, and to the line across it
+            let end_distance = (-t).max(t - length);
+            let side_distance = (offset - along * t).length();
+
+            let coverage = (half_width + 0.5 - side_distance).clamp(0.0, 1.0)
+                * (0.5 - end_distance).clamp(0.0, 1.0);
+            if coverage > 0.0 {
+                let screen = unpack_pixel(dst.read([x, y]));
+                dst.write(x, y, pack_pixel(over(screen, color * coverage)));
+            }
+        }
+    }
+}
+
+/// Clears `buffer` to transparency except for a border `margin` pixels wide on every side, e.g. to
+/// keep static chrome drawn once around the area [`Painter::paint`] draws into each frame.
+pub fn clear_interior(buffer: &mut Buffer2d<u32>, margin: usize) {
+    let [width, height] = buffer.size();
+    let interior = [
+        margin,
+        margin,
+        width.saturating_sub(2 * margin),
+        height.saturating_sub(2 * margin),
+    ];
+    diff::clear_region(buffer, interior);
+}
+
+/// Stacks `layers` bottom to top into a new buffer of `size` pixels, starting from transparency.
+/// Each layer is blended with its [`BlendOp`] at its offset, and clipped to the output.
+pub fn composite(
+    size: [usize; 2],
+    layers: &[(&Buffer2d<u32>, BlendOp, [i32; 2])],
+) -> Buffer2d<u32> {
+    let mut output = Buffer2d::fill(size, 0);
+    for &(layer, op, offset) in layers {
+        blit_with(&mut output, layer, offset, |below, layer| {
+            pack_pixel(op.blend(unpack_pixel(below), unpack_pixel(layer)))
+        });
+    }
+    output
+}
+
+/// Hashes the size and pixels of a buffer.
+fn hash_buffer(buffer: &Buffer2d<u32>) -> u64 {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    buffer.size().hash(&mut hasher);
+    buffer.raw().hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Helper to provide an image given successive egui::RawInputs
+pub struct SoftwareGui {
+    pub egui_ctx: egui::Context,
+    pub software_render: Painter,
+    fonts: egui::FontDefinitions,
+    /// Hash of the pixels of the previous frame, to detect repaints which changed nothing.
+    last_frame_hash: Option<u64>,
+    /// Scale overriding the one reported by the input, see [`SoftwareGui::set_pixels_per_point`].
+    pixels_per_point: Option<f32>,
+    /// When the last painted frame asked to be repainted, see [`SoftwareGui::repaint_delay`].
+    repaint_delay: std::time::Duration,
+    /// Context laying out [`SoftwareGui::measure`], so measuring leaves the state of `egui_ctx`
+    /// alone. Created on first use, with the same fonts.
+    measure_ctx: Option<egui::Context>,
+}
+
+impl SoftwareGui {
+    pub fn new() -> Self {
+        Self {
+            egui_ctx: Default::default(),
+            software_render: Painter::new(),
+            fonts: Default::default(),
+            last_frame_hash: None,
+            pixels_per_point: None,
+            repaint_delay: std::time::Duration::MAX,
+            measure_ctx: None,
+        }
+    }
+
+    /// Lays the GUI out at `pixels_per_point` from the next update on, whatever scale the input
+    /// reports, or goes back to the input's scale when `None`. The screen rect of the input is then
+    /// derived from the painted size. The scale is applied as the native scale of the viewport, so
+    /// zooming the GUI (e.g. with Ctrl + +) still works on top of it.
+    pub fn set_pixels_per_point(&mut self, pixels_per_point: Option<f32>) {
+        self.pixels_per_point = pixels_per_point;
+    }
+
+    /// Applies settings of this GUI which override parts of the host's input, for painting at
+    /// `screen_size` pixels.
+    fn prepare_input(&self, input: &mut egui::RawInput, [width, height]: [usize; 2]) {
+        if let Some(pixels_per_point) = self.pixels_per_point {
+            // The host's screen rect is in points of its own scale
+            input.screen_rect = Some(egui::Rect::from_min_size(
+                egui::Pos2::ZERO,
+                egui::vec2(width as f32, height as f32) / pixels_per_point,
+            ));
+            input
+                .viewports
+                .entry(input.viewport_id)
+                .or_default()
+                .native_pixels_per_point = Some(pixels_per_point);
+        }
+    }
+
+    /// Replaces the fonts of the context. The new font atlas is uploaded on the next update.
+    pub fn set_fonts(&mut self, fonts: egui::FontDefinitions) {
+        self.fonts = fonts;
+        self.egui_ctx.set_fonts(self.fonts.clone());
+        self.measure_ctx = None;
+    }
+
+    /// Adds a font, preferred over the existing ones for proportional text and used as a fallback
+    /// for monospace text.
+    pub fn add_font(&mut self, name: impl Into<String>, data: egui::FontData) {
+        let name = name.into();
+        self.fonts
+            .font_data
+            .insert(name.clone(), std::sync::Arc::new(data));
+        self.fonts
+            .families
+            .entry(egui::FontFamily::Proportional)
+            .or_default()
+            .insert(0, name.clone());
+        self.fonts
+            .families
+            .entry(egui::FontFamily::Monospace)
+            .or_default()
+            .push(name);
+        self.egui_ctx.set_fonts(self.fonts.clone());
+        self.measure_ctx = None;
+    }
+
+    pub fn update(
+        &mut self,
+        new_input: egui::RawInput,
+        screen_size: [usize; 2],
+        sub_gui: impl FnMut(&egui::Context),
+    ) -> egui::ColorImage {
+        let buffer = self.paint(new_input, screen_size, sub_gui);
+        // Whatever is presented now, the next frame can't be compared against the last hash
+        self.last_frame_hash = None;
         euc_to_egui_colorimage(buffer)
     }
+
+    /// Like [`SoftwareGui::update`], with the input built from winit `events` by `input`, at the
+    /// window's physical size.
+    #[cfg(feature = "winit")]
+    pub fn update_with_winit_events(
+        &mut self,
+        input: &mut WinitInput,
+        events: &[::winit::event::WindowEvent],
+        sub_gui: impl FnMut(&egui::Context),
+    ) -> egui::ColorImage {
+        for event in events {
+            input.handle_event(event);
+        }
+        let screen_size = input.screen_size();
+        self.update(input.take_raw_input(), screen_size, sub_gui)
+    }
+
+    /// Lays out `ui_builder` in an unconstrained area and returns the size it used in points, e.g. to
+    /// size the buffer for [`SoftwareGui::update`] to fit the content.
+    ///
+    /// This runs a pass of a separate context with the same fonts, style and scale, without
+    /// tessellating or painting anything. The frame count, memory, animations and input state of
+    /// [`SoftwareGui::egui_ctx`] are left untouched, so widgets keeping state in memory (e.g.
+    /// collapsed headers) are measured in their default state.
+    pub fn measure(&mut self, mut ui_builder: impl FnMut(&mut egui::Ui)) -> egui::Vec2 {
+        let mut input = egui::RawInput::default();
+        let pixels_per_point = self
+            .pixels_per_point
+            .unwrap_or_else(|| self.egui_ctx.pixels_per_point());
+        input
+            .viewports
+            .entry(input.viewport_id)
+            .or_default()
+            .native_pixels_per_point = Some(pixels_per_point);
+
+        let ctx = self.measure_ctx.get_or_insert_with(|| {
+            let ctx = egui::Context::default();
+            ctx.set_fonts(self.fonts.clone());
+            ctx
+        });
+        ctx.set_style(self.egui_ctx.style());
+
+        let mut size = egui::Vec2::ZERO;
+        // The output only holds what the measuring context would paint
+        let _ = ctx.run(input, |ctx| {
+            size = egui::Area::new(egui::Id::new("egui_euc_measure"))
+                .constrain(false)
+                .show(ctx, &mut ui_builder)
+                .response
+                .rect
+                .size();
+        });
+        size
+    }
+
+    /// How long after the last update egui asked to be updated again, e.g. to sleep until then in
+    /// a power-efficient loop. Zero while animations are running, and [`Duration::MAX`] when the
+    /// GUI is idle and only needs updating on new input.
+    ///
+    /// [`Duration::MAX`]: std::time::Duration::MAX
+    pub fn repaint_delay(&self) -> std::time::Duration {
+        self.repaint_delay
+    }
+
+    /// Whether egui asked to be updated again right away, see [`SoftwareGui::repaint_delay`].
+    pub fn needs_repaint(&self) -> bool {
+        self.repaint_delay.is_zero()
+    }
+
+    fn record_repaint_delay(&mut self, output: &egui::FullOutput) {
+        self.repaint_delay = output
+            .viewport_output
+            .get(&egui::ViewportId::ROOT)
+            .map_or(std::time::Duration::MAX, |viewport| viewport.repaint_delay);
+    }
+
+    /// Like [`SoftwareGui::update`], but returns `None` if the rendered image is identical to the
+    /// previous frame's, so repaints which changed nothing visible need not be presented.
+    pub fn update_if_changed(
+        &mut self,
+        new_input: egui::RawInput,
+        screen_size: [usize; 2],
+        sub_gui: impl FnMut(&egui::Context),
+    ) -> Option<egui::ColorImage> {
+        let buffer = self.paint(new_input, screen_size, sub_gui);
+        let hash = hash_buffer(&buffer);
+        let changed = self.last_frame_hash.replace(hash) != Some(hash);
+        changed.then(|| euc_to_egui_colorimage(buffer))
+    }
+
+    /// Runs and paints a frame.
+    fn paint(
+        &mut self,
+        mut new_input: egui::RawInput,
+        screen_size: [usize; 2],
+        sub_gui: impl FnMut(&egui::Context),
+    ) -> Buffer2d<u32> {
+        self.prepare_input(&mut new_input, screen_size);
+        let output = self.egui_ctx.run(new_input, sub_gui);
+        self.record_repaint_delay(&output);
+        // The scale the frame was laid out at; the context's may already have changed for the next one
+        let pixels_per_point = output.pixels_per_point;
+        let clipped_primitives = self.egui_ctx.tessellate(output.shapes, pixels_per_point);
+        self.software_render.paint_and_update_textures(
+            &output.textures_delta,
+            &clipped_primitives,
+            pixels_per_point,
+            screen_size,
+        )
+    }
+
+    /// Like [`SoftwareGui::update`], uploading the rendered image straight into `texture` of a
+    /// parent egui context, for the common case of showing the sub-GUI as an image in another GUI.
+    ///
+    /// The texture is only re-uploaded if the image changed, which is what the returned `bool` reports.
+    pub fn update_texture(
+        &mut self,
+        texture: &mut egui::TextureHandle,
+        options: TextureOptions,
+        new_input: egui::RawInput,
+        screen_size: [usize; 2],
+        sub_gui: impl FnMut(&egui::Context),
+    ) -> bool {
+        match self.update_if_changed(new_input, screen_size, sub_gui) {
+            Some(image) => {
+                texture.set(image, options);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Like [`SoftwareGui::update`], but paints the layers for which `layer_filter` returns true
+    /// and all other layers into two separate images, e.g. to show tooltips and popups on a
+    /// different target than the rest of the GUI.
+    pub fn update_layers(
+        &mut self,
+        mut new_input: egui::RawInput,
+        screen_size: [usize; 2],
+        layer_filter: impl Fn(egui::LayerId) -> bool,
+        mut sub_gui: impl FnMut(&egui::Context),
+    ) -> (egui::ColorImage, egui::ColorImage) {
+        // Shapes no longer know their layer once the pass has ended, so split them up before that
+        self.prepare_input(&mut new_input, screen_size);
+        self.egui_ctx.begin_pass(new_input);
+        sub_gui(&self.egui_ctx);
+        let (mut matching, mut rest) = self.egui_ctx.graphics(|graphics| {
+            let mut matching = egui::layers::GraphicLayers::default();
+            let mut rest = egui::layers::GraphicLayers::default();
+            for layer_id in graphics.layer_ids() {
+                let split = if layer_filter(layer_id) {
+                    &mut matching
+                } else {
+                    &mut rest
+                };
+                let Some(paint_list) = graphics.get(layer_id) else {
+                    continue;
+                };
+                let entry = split.entry(layer_id);
+                for clipped in paint_list.all_entries() {
+                    entry.add(clipped.clip_rect, clipped.shape.clone());
+                }
+            }
+            (matching, rest)
+        });
+        let output = self.egui_ctx.end_pass();
+        self.record_repaint_delay(&output);
+        self.last_frame_hash = None;
+
+        let (order, to_global) = self
+            .egui_ctx
+            .memory(|mem| (mem.areas().order().to_vec(), mem.to_global.clone()));
+        let pixels_per_point = output.pixels_per_point;
+
+        let matching = self
+            .egui_ctx
+            .tessellate(matching.drain(&order, &to_global), pixels_per_point);
+        let rest = self
+            .egui_ctx
+            .tessellate(rest.drain(&order, &to_global), pixels_per_point);
+
+        // One frame for both images, so textures freed by this delta are still there for the rest
+        let mut matching_image = Buffer2d::fill(screen_size, 0);
+        let mut rest_image = Buffer2d::fill(screen_size, 0);
+        self.software_render.begin_frame(&output.textures_delta);
+        self.software_render
+            .paint(&mut matching_image, &matching, pixels_per_point);
+        self.software_render
+            .paint(&mut rest_image, &rest, pixels_per_point);
+        self.software_render.end_frame();
+
+        (
+            euc_to_egui_colorimage(matching_image),
+            euc_to_egui_colorimage(rest_image),
+        )
+    }
 }
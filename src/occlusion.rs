@@ -0,0 +1,228 @@
+//! Skipping work hidden under opaque rectangles drawn later in the frame, see
+//! [`Painter::set_occlusion_culling`](crate::Painter::set_occlusion_culling).
+//!
+//! Blending order is never changed. Instead, before drawing, every primitive is checked against
+//! the opaque rectangles of the primitives after it: pixels under those would be overwritten
+//! anyway, so they are not written, and primitives entirely under one of them are not drawn at all.
+
+use std::collections::HashMap;
+
+use egui::{epaint, ClippedPrimitive, TextureId};
+use euc::{Target, Texture};
+
+use crate::diff::primitive_bounds;
+use crate::texture::SoftwareTexture;
+use crate::{remap_texture_id, validate_mesh, Affine2, Scissor, TextureIdRemap};
+
+/// How a primitive is hidden by opaque primitives drawn after it.
+#[derive(Default)]
+pub(crate) struct Occlusion {
+    /// Opaque rectangles drawn later which overlap the primitive, as `[x, y, width, height]`.
+    pub(crate) rects: Vec<[usize; 4]>,
+    /// Whether one of `rects` covers all of the primitive.
+    pub(crate) hidden: bool,
+}
+
+/// Finds the [`Occlusion`] of each of `primitives`, drawn only inside of `limit`, into the first
+/// `primitives.len()` entries of `occlusions`. Both `occlusions` and `occluders` are scratch space
+/// whose allocations are reused.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn find_occlusions(
+    primitives: &[&ClippedPrimitive],
+    textures: &HashMap<TextureId, SoftwareTexture>,
+    texture_id_remap: Option<&TextureIdRemap>,
+    pixel_scale: egui::Vec2,
+    to_target: Affine2,
+    screen_size: [usize; 2],
+    limit: [usize; 4],
+    occlusions: &mut Vec<Occlusion>,
+    occluders: &mut Vec<[usize; 4]>,
+) {
+    if occlusions.len() < primitives.len() {
+        occlusions.resize_with(primitives.len(), Occlusion::default);
+    }
+    let occlusions = &mut occlusions[..primitives.len()];
+    for occlusion in occlusions.iter_mut() {
+        occlusion.rects.clear();
+        occlusion.hidden = false;
+    }
+    occluders.clear();
+
+    for (item, occlusion) in primitives.iter().zip(occlusions).rev() {
+        let epaint::Primitive::Mesh(mesh) = &item.primitive else {
+            continue;
+        };
+        let clip_rect = to_target.apply_rect(item.clip_rect);
+        let scissor =
+            Scissor::from_clip_rect((), screen_size, pixel_scale, clip_rect).restrict(limit);
+        let scissor = [scissor.x, scissor.y, scissor.width, scissor.height];
+        let bounds = intersect(
+            primitive_bounds(item, pixel_scale, to_target, screen_size),
+            scissor,
+        );
+        if bounds[2] == 0 || bounds[3] == 0 {
+            continue;
+        }
+
+        occlusion.rects.extend(
+            occluders
+                .iter()
+                .copied()
+                .filter(|&rect| overlaps(rect, bounds)),
+        );
+        occlusion.hidden = occlusion.rects.iter().any(|&rect| contains(rect, bounds));
+
+        // Invalid meshes are skipped when drawing, so they hide nothing
+        let opaque = textures
+            .get(&remap_texture_id(texture_id_remap, mesh.texture_id))
+            .filter(|_| validate_mesh(mesh).is_ok())
+            .and_then(|texture| opaque_rect(mesh, texture, pixel_scale, to_target));
+        if let Some(rect) = opaque.map(|rect| intersect(rect, scissor)) {
+            if rect[2] > 0 && rect[3] > 0 && !occlusion.hidden {
+                occluders.push(rect);
+            }
+        }
+    }
+}
+
+/// The pixels a mesh is sure to cover with opaque color, if it is an axis-aligned rectangle
+/// filled from a single opaque texel, like the solid fills egui tessellates (optionally with a
+/// transparent feathering ring around them). The rectangle must be filled by two triangles
+/// between its corners, not just have opaque vertices there.
+fn opaque_rect(
+    mesh: &epaint::Mesh,
+    texture: &SoftwareTexture,
+    pixel_scale: egui::Vec2,
+    to_target: Affine2,
+) -> Option<[usize; 4]> {
+    let mut opaque = mesh.vertices.iter().filter(|v| v.color.a() == 255);
+    let first = *opaque.next()?;
+    let (mut min, mut max) = (first.pos, first.pos);
+    let mut count = 1;
+    for vertex in opaque {
+        if vertex.uv != first.uv {
+            return None;
+        }
+        min = min.min(vertex.pos);
+        max = max.max(vertex.pos);
+        count += 1;
+    }
+
+    // The four opaque vertices must be the four corners of their bounding box
+    let corners = [min, egui::pos2(max.x, min.y), max, egui::pos2(min.x, max.y)];
+    let is_rect = count == 4
+        && min.x < max.x
+        && min.y < max.y
+        && corners.iter().all(|&corner| {
+            mesh.vertices
+                .iter()
+                .any(|v| v.color.a() == 255 && v.pos == corner)
+        });
+    if !is_rect {
+        return None;
+    }
+
+    // Two of the triangles must tile the rectangle by splitting it along a diagonal, so each of
+    // them leaves out one of two opposite corners
+    let corner = |index: u32| {
+        let vertex = mesh.vertices[index as usize];
+        (vertex.color.a() == 255)
+            .then(|| corners.iter().position(|&corner| corner == vertex.pos))
+            .flatten()
+    };
+    let mut left_out = [false; 4];
+    for triangle in mesh.indices.chunks_exact(3) {
+        let [Some(a), Some(b), Some(c)] = [triangle[0], triangle[1], triangle[2]].map(corner)
+        else {
+            continue;
+        };
+        if a != b && b != c && a != c {
+            left_out[6 - a - b - c] = true;
+        }
+    }
+    if !(left_out[0] && left_out[2] || left_out[1] && left_out[3]) {
+        return None;
+    }
+
+    let [width, height] = texture.pixels.size();
+    if width == 0 || height == 0 {
+        return None;
+    }
+    let texel = [
+        ((first.uv.x * width as f32) as usize).min(width - 1),
+        ((first.uv.y * height as f32) as usize).min(height - 1),
+    ];
+    if texture.pixels.read(texel).a() < 1.0 {
+        return None;
+    }
+
+    // Only called for transforms which keep rectangles axis-aligned
+    let rect = to_target.apply_rect(egui::Rect::from_min_max(min, max));
+    let (min, max) = (rect.min, rect.max);
+
+    // Pixels whose centers, which is where the rasterizer samples, are certainly inside
+    let min_x = (min.x * pixel_scale.x).ceil().max(0.0) as usize;
+    let min_y = (min.y * pixel_scale.y).ceil().max(0.0) as usize;
+    let max_x = ((max.x * pixel_scale.x).floor().max(0.0) as usize).max(min_x);
+    let max_y = ((max.y * pixel_scale.y).floor().max(0.0) as usize).max(min_y);
+    Some([min_x, min_y, max_x - min_x, max_y - min_y])
+}
+
+pub(crate) fn intersect(a: [usize; 4], b: [usize; 4]) -> [usize; 4] {
+    let min_x = a[0].max(b[0]);
+    let min_y = a[1].max(b[1]);
+    let max_x = (a[0] + a[2]).min(b[0] + b[2]).max(min_x);
+    let max_y = (a[1] + a[3]).min(b[1] + b[3]).max(min_y);
+    [min_x, min_y, max_x - min_x, max_y - min_y]
+}
+
+fn overlaps(a: [usize; 4], b: [usize; 4]) -> bool {
+    let [_, _, width, height] = intersect(a, b);
+    width > 0 && height > 0
+}
+
+fn contains(outer: [usize; 4], inner: [usize; 4]) -> bool {
+    outer[0] <= inner[0]
+        && outer[1] <= inner[1]
+        && inner[0] + inner[2] <= outer[0] + outer[2]
+        && inner[1] + inner[3] <= outer[1] + outer[3]
+}
+
+/// Wrapper of a euc::Target skipping writes inside of any of `rects`.
+pub(crate) struct Occluded<'o, T> {
+    pub(crate) inner: T,
+    pub(crate) rects: &'o [[usize; 4]],
+}
+
+impl<T, const N: usize> Texture<N> for Occluded<'_, T>
+where
+    T: Texture<N>,
+{
+    type Index = T::Index;
+    type Texel = T::Texel;
+
+    fn size(&self) -> [Self::Index; N] {
+        self.inner.size()
+    }
+
+    fn read(&self, index: [Self::Index; N]) -> Self::Texel {
+        self.inner.read(index)
+    }
+}
+
+impl<T: Target> Target for Occluded<'_, T> {
+    unsafe fn read_exclusive_unchecked(&self, x: usize, y: usize) -> Self::Texel {
+        unsafe { self.inner.read_exclusive_unchecked(x, y) }
+    }
+
+    unsafe fn write_exclusive_unchecked(&self, x: usize, y: usize, texel: Self::Texel) {
+        let occluded = self.rects.iter().any(|&[rx, ry, width, height]| {
+            x.wrapping_sub(rx) < width && y.wrapping_sub(ry) < height
+        });
+        if !occluded {
+            unsafe {
+                self.inner.write_exclusive_unchecked(x, y, texel);
+            }
+        }
+    }
+}
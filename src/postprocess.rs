@@ -0,0 +1,93 @@
+//! Color transforms applied to the painted output, see
+//! [`Painter::set_post_process`](crate::Painter::set_post_process).
+
+use egui::Rgba;
+use euc::{Buffer2d, Target, Texture};
+
+use crate::{pack_pixel, unpack_pixel};
+
+/// A kind of dichromacy, for [`PostProcess`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorBlindness {
+    /// Missing long-wavelength (red) cones.
+    Protanopia,
+    /// Missing medium-wavelength (green) cones.
+    Deuteranopia,
+    /// Missing short-wavelength (blue) cones.
+    Tritanopia,
+}
+
+impl ColorBlindness {
+    /// Linear RGB simulation matrix from Machado, Oliveira & Fernandes (2009), at full severity.
+    fn simulation(self) -> [[f32; 3]; 3] {
+        match self {
+            ColorBlindness::Protanopia => [
+                [0.152286, 1.052583, -0.204868],
+                [0.114503, 0.786281, 0.099216],
+                [-0.003882, -0.048116, 1.051998],
+            ],
+            ColorBlindness::Deuteranopia => [
+                [0.367322, 0.860646, -0.227968],
+                [0.280085, 0.672501, 0.047413],
+                [-0.011820, 0.042940, 0.968881],
+            ],
+            ColorBlindness::Tritanopia => [
+                [1.255528, -0.076749, -0.178779],
+                [-0.078411, 0.930809, 0.147602],
+                [0.004733, 0.691367, 0.303900],
+            ],
+        }
+    }
+}
+
+/// A transform of the whole output, applied in linear color once everything is drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PostProcess {
+    /// Shows the output as someone with the given color blindness would see it.
+    ColorBlind(ColorBlindness),
+    /// Shifts the colors someone with the given color blindness can't tell apart towards ones
+    /// they can (daltonization), as a correction rather than a simulation.
+    Daltonize(ColorBlindness),
+}
+
+impl PostProcess {
+    /// Transforms linear premultiplied `color`. The transforms are linear, so they apply to
+    /// premultiplied color as they would to straight color.
+    pub fn apply(self, color: Rgba) -> Rgba {
+        let rgb = [color.r(), color.g(), color.b()];
+        let [r, g, b] = match self {
+            PostProcess::ColorBlind(kind) => transform(kind.simulation(), rgb),
+            PostProcess::Daltonize(kind) => {
+                let simulated = transform(kind.simulation(), rgb);
+                let error = [0, 1, 2].map(|i| rgb[i] - simulated[i]);
+                // Moves the lost information onto the channels which are still perceived
+                let shift = [[0.0, 0.0, 0.0], [0.7, 1.0, 0.0], [0.7, 0.0, 1.0]];
+                let shift = transform(shift, error);
+                [0, 1, 2].map(|i| rgb[i] + shift[i])
+            }
+        };
+        let a = color.a();
+        Rgba::from_rgba_premultiplied(r.clamp(0.0, a), g.clamp(0.0, a), b.clamp(0.0, a), a)
+    }
+}
+
+fn transform(matrix: [[f32; 3]; 3], [r, g, b]: [f32; 3]) -> [f32; 3] {
+    matrix.map(|[mr, mg, mb]| mr * r + mg * g + mb * b)
+}
+
+/// Applies `post_process` to the `[x, y, width, height]` rectangle of `buffer`.
+pub(crate) fn apply_region(
+    buffer: &mut Buffer2d<u32>,
+    post_process: PostProcess,
+    [x, y, width, height]: [usize; 4],
+) {
+    for y in y..y + height {
+        for x in x..x + width {
+            let pixel = buffer.read([x, y]);
+            // Nothing to transform in empty pixels
+            if pixel.to_le_bytes()[3] != 0 {
+                buffer.write(x, y, pack_pixel(post_process.apply(unpack_pixel(pixel))));
+            }
+        }
+    }
+}
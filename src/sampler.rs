@@ -0,0 +1,156 @@
+//! Texture samplers used by [`EguiMeshEucPipeline`](crate::EguiMeshEucPipeline).
+
+use egui::{Rgba, TextureWrapMode};
+use euc::{Sampler, Texture};
+
+use crate::color::{decode_srgb, encode_srgb};
+
+/// Something which can be sampled at a UV coordinate, producing linear premultiplied color.
+///
+/// Implemented for every euc [`Sampler`] with matching index and sample types.
+pub trait UvSampler {
+    fn sample_uv(&self, uv: [f32; 2]) -> Rgba;
+
+    /// Returns `border` for UVs outside of `[0, 1]` instead of sampling, like a GPU's
+    /// `ClampToBorder` address mode.
+    fn bordered(self, border: Rgba) -> Bordered<Self>
+    where
+        Self: Sized,
+    {
+        Bordered {
+            sampler: self,
+            border,
+        }
+    }
+}
+
+impl<S> UvSampler for S
+where
+    S: Sampler<2, Index = f32, Sample = Rgba>,
+{
+    #[inline(always)]
+    fn sample_uv(&self, uv: [f32; 2]) -> Rgba {
+        self.sample(uv)
+    }
+}
+
+/// Bilinear filtering matching GPU samplers: texel centers sit at `(i + 0.5) / size`, and the wrap
+/// mode applies to each of the four texels read rather than to the UV. A magnified image's edges
+/// therefore line up with its mesh's edges, and `Repeat` blends across the seam.
+pub struct Bilinear<T> {
+    pub texture: T,
+    pub wrap_mode: TextureWrapMode,
+}
+
+impl<T> Bilinear<T> {
+    pub fn new(texture: T, wrap_mode: TextureWrapMode) -> Self {
+        Self { texture, wrap_mode }
+    }
+}
+
+impl<T> UvSampler for Bilinear<T>
+where
+    T: Texture<2, Index = usize, Texel = Rgba>,
+{
+    fn sample_uv(&self, [u, v]: [f32; 2]) -> Rgba {
+        let [width, height] = self.texture.size();
+        if width == 0 || height == 0 {
+            return Rgba::TRANSPARENT;
+        }
+
+        let x = u * width as f32 - 0.5;
+        let y = v * height as f32 - 0.5;
+        let (left, top) = (x.floor(), y.floor());
+        let (fx, fy) = (x - left, y - top);
+        let (left, top) = (left as i64, top as i64);
+
+        let read = |x: i64, y: i64| {
+            self.texture.read([
+                wrap_texel(x, width, self.wrap_mode),
+                wrap_texel(y, height, self.wrap_mode),
+            ])
+        };
+
+        let upper = read(left, top) * (1.0 - fx) + read(left.saturating_add(1), top) * fx;
+        let lower = read(left, top.saturating_add(1)) * (1.0 - fx)
+            + read(left.saturating_add(1), top.saturating_add(1)) * fx;
+        upper * (1.0 - fy) + lower * fy
+    }
+}
+
+/// Like [`Bilinear`], but interpolates the sRGB encoding of the texels and decodes the result, to
+/// match GPU setups which filter sRGB textures without decoding them first. Blends between
+/// distinct colors come out darker than with [`Bilinear`], which interpolates linear color.
+pub struct SrgbBilinear<T> {
+    pub texture: T,
+    pub wrap_mode: TextureWrapMode,
+}
+
+impl<T> SrgbBilinear<T> {
+    pub fn new(texture: T, wrap_mode: TextureWrapMode) -> Self {
+        Self { texture, wrap_mode }
+    }
+}
+
+impl<T> UvSampler for SrgbBilinear<T>
+where
+    T: Texture<2, Index = usize, Texel = Rgba>,
+{
+    fn sample_uv(&self, uv: [f32; 2]) -> Rgba {
+        decode_srgb(Bilinear::new(SrgbEncoded(&self.texture), self.wrap_mode).sample_uv(uv))
+    }
+}
+
+/// A texture read as the sRGB encoding of its texels, see [`encode_srgb`].
+struct SrgbEncoded<'t, T>(&'t T);
+
+impl<T> Texture<2> for SrgbEncoded<'_, T>
+where
+    T: Texture<2, Index = usize, Texel = Rgba>,
+{
+    type Index = usize;
+    type Texel = Rgba;
+
+    fn size(&self) -> [usize; 2] {
+        self.0.size()
+    }
+
+    fn read(&self, index: [usize; 2]) -> Rgba {
+        encode_srgb(self.0.read(index))
+    }
+}
+
+/// A sampler returning a fixed color outside of `[0, 1]`, see [`UvSampler::bordered`].
+pub struct Bordered<S> {
+    pub sampler: S,
+    /// Linear premultiplied color returned for out of range UVs.
+    pub border: Rgba,
+}
+
+impl<S: UvSampler> UvSampler for Bordered<S> {
+    fn sample_uv(&self, [u, v]: [f32; 2]) -> Rgba {
+        if (0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v) {
+            self.sampler.sample_uv([u, v])
+        } else {
+            self.border
+        }
+    }
+}
+
+/// Brings texel coordinate `i` into `0..size` according to `wrap_mode`.
+pub(crate) fn wrap_texel(i: i64, size: usize, wrap_mode: TextureWrapMode) -> usize {
+    let size = size as i64;
+    let wrapped = match wrap_mode {
+        TextureWrapMode::ClampToEdge => i.clamp(0, size - 1),
+        TextureWrapMode::Repeat => i.rem_euclid(size),
+        TextureWrapMode::MirroredRepeat => {
+            let i = i.rem_euclid(2 * size);
+            if i < size {
+                i
+            } else {
+                2 * size - 1 - i
+            }
+        }
+    };
+    wrapped as usize
+}
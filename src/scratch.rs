@@ -0,0 +1,75 @@
+//! Per-frame scratch data, kept on the [`Painter`](crate::Painter) so steady-state rendering
+//! reuses the same allocations every frame.
+
+use egui::{ClippedPrimitive, Pos2};
+use euc::{Buffer2d, Texture};
+
+use crate::occlusion::Occlusion;
+
+#[derive(Default)]
+pub(crate) struct Scratch {
+    /// Scissor rectangles drawn this frame, for [`DebugMode::ClipRects`](crate::DebugMode::ClipRects).
+    pub(crate) clip_rects: Vec<[usize; 4]>,
+    /// Triangles drawn this frame in pixels, with the scissor rectangle each was clipped to, for
+    /// [`DebugMode::Wireframe`](crate::DebugMode::Wireframe).
+    pub(crate) wireframe: Vec<([usize; 4], [Pos2; 3])>,
+    /// Indices of the mesh being drawn without its degenerate triangles.
+    pub(crate) indices: Vec<u32>,
+    /// Primitives of the frame being drawn, collected for occlusion culling. Always empty between
+    /// frames, so the borrows it held are gone; see [`recycle`].
+    pub(crate) primitives: Vec<&'static ClippedPrimitive>,
+    /// Occlusion of each primitive of the frame being drawn, of which only the first
+    /// `primitives.len()` are current. Entries are kept to reuse their rectangles.
+    pub(crate) occlusions: Vec<Occlusion>,
+    /// Opaque rectangles found so far while looking for occlusions.
+    pub(crate) occluders: Vec<[usize; 4]>,
+    /// Primitives of [`Painter::paint_to`](crate::Painter::paint_to), collected to find the
+    /// pixels they can touch before drawing them. Always empty between calls, like `primitives`.
+    pub(crate) staged: Vec<&'static ClippedPrimitive>,
+    /// Staging buffer of [`Painter::paint_to`](crate::Painter::paint_to) and frame buffer of
+    /// [`Painter::paint_with`](crate::Painter::paint_with), put back after use.
+    pub(crate) color: Option<Buffer2d<u32>>,
+}
+
+impl Scratch {
+    /// Resets everything for a new frame.
+    pub(crate) fn begin(&mut self) {
+        self.clip_rects.clear();
+        self.wireframe.clear();
+    }
+
+    /// Takes the staging buffer, reallocated if it isn't `size` pixels. Its contents are unspecified.
+    pub(crate) fn take_color(&mut self, size: [usize; 2]) -> Buffer2d<u32> {
+        match self.color.take() {
+            Some(color) if color.size() == size => color,
+            _ => Buffer2d::fill(size, 0),
+        }
+    }
+}
+
+/// Empties `vec` and reuses its allocation for elements of type `U`, e.g. to store a `Vec` of
+/// references between frames with a lifetime the references it held during the frame didn't have.
+/// Collecting from a `Vec`'s own iterator reuses its allocation when `T` and `U` have the same
+/// layout, as references to the same type do.
+pub(crate) fn recycle<T, U>(mut vec: Vec<T>) -> Vec<U> {
+    vec.clear();
+    vec.into_iter().map(|_| unreachable!()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recycling_keeps_the_allocation() {
+        let value = 1;
+        let mut vec = Vec::with_capacity(16);
+        vec.push(&value);
+        let (ptr, capacity) = (vec.as_ptr().cast::<()>(), vec.capacity());
+
+        let recycled: Vec<&'static i32> = recycle(vec);
+        assert!(recycled.is_empty());
+        assert_eq!(recycled.capacity(), capacity);
+        assert_eq!(recycled.as_ptr().cast::<()>(), ptr);
+    }
+}
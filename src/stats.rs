@@ -0,0 +1,77 @@
+//! Counters of the work done painting a frame.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use euc::{Target, Texture};
+
+/// Work done painting the last frame, see [`Painter::paint_stats`](crate::Painter::paint_stats).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PaintStats {
+    /// Meshes drawn, not counting those clipped away entirely.
+    pub meshes: usize,
+    /// Triangles submitted to the rasterizer by the meshes drawn, not counting
+    /// `degenerate_triangles`.
+    pub triangles: usize,
+    /// Triangles skipped for having (almost) no area, see
+    /// [`Painter::set_degenerate_triangle_area`](crate::Painter::set_degenerate_triangle_area).
+    pub degenerate_triangles: usize,
+    /// Meshes skipped because opaque rectangles drawn later hide them, see
+    /// [`Painter::set_occlusion_culling`](crate::Painter::set_occlusion_culling).
+    pub occluded_meshes: usize,
+    /// Meshes skipped because they failed [`validate_mesh`](crate::validate_mesh).
+    pub invalid_meshes: usize,
+    /// Output pixels written, counting a pixel once for every mesh drawn over it. Only counted
+    /// while enabled with [`Painter::set_count_pixels`](crate::Painter::set_count_pixels).
+    pub pixels_written: usize,
+}
+
+impl PaintStats {
+    /// Bytes written to the output, for estimating memory bandwidth. Blending reads every pixel
+    /// before writing it, so the total traffic to the output is twice this.
+    pub fn bytes_written(&self) -> usize {
+        self.pixels_written * std::mem::size_of::<u32>()
+    }
+}
+
+/// Wrapper of a euc::Target of output pixels counting the writes made to it, if given a counter,
+/// and recording in `visible` whether any of them wasn't fully transparent.
+pub(crate) struct CountWrites<'c, T> {
+    pub(crate) inner: T,
+    pub(crate) count: Option<&'c AtomicUsize>,
+    pub(crate) visible: &'c AtomicBool,
+}
+
+impl<T, const N: usize> Texture<N> for CountWrites<'_, T>
+where
+    T: Texture<N>,
+{
+    type Index = T::Index;
+    type Texel = T::Texel;
+
+    fn size(&self) -> [Self::Index; N] {
+        self.inner.size()
+    }
+
+    fn read(&self, index: [Self::Index; N]) -> Self::Texel {
+        self.inner.read(index)
+    }
+}
+
+impl<T: Target<Texel = u32>> Target for CountWrites<'_, T> {
+    unsafe fn read_exclusive_unchecked(&self, x: usize, y: usize) -> Self::Texel {
+        unsafe { self.inner.read_exclusive_unchecked(x, y) }
+    }
+
+    unsafe fn write_exclusive_unchecked(&self, x: usize, y: usize, texel: Self::Texel) {
+        if let Some(count) = self.count {
+            count.fetch_add(1, Ordering::Relaxed);
+        }
+        // Checking first keeps threads from writing the shared flag for every pixel
+        if texel.to_le_bytes()[3] != 0 && !self.visible.load(Ordering::Relaxed) {
+            self.visible.store(true, Ordering::Relaxed);
+        }
+        unsafe {
+            self.inner.write_exclusive_unchecked(x, y, texel);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Painting into pixel storage other than a euc [`Buffer2d`].
+
+use egui::Color32;
+use euc::{Buffer2d, Target, Texture};
+
+use crate::color::over;
+use crate::{pack_pixel, unpack_pixel};
+
+/// Pixel storage a [`Painter`](crate::Painter) can paint into with
+/// [`Painter::paint_to`](crate::Painter::paint_to), e.g. a memory-mapped framebuffer. Pixels are
+/// output pixels, see [`unpack_pixel`](crate::unpack_pixel).
+pub trait RenderTarget {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn get_pixel(&self, x: usize, y: usize) -> u32;
+    fn set_pixel(&mut self, x: usize, y: usize, pixel: u32);
+}
+
+impl RenderTarget for Buffer2d<u32> {
+    fn width(&self) -> usize {
+        self.size()[0]
+    }
+
+    fn height(&self) -> usize {
+        self.size()[1]
+    }
+
+    fn get_pixel(&self, x: usize, y: usize) -> u32 {
+        self.read([x, y])
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, pixel: u32) {
+        self.write(x, y, pixel);
+    }
+}
+
+/// Packed 24-bit RGB pixel storage, 3 bytes per pixel in rows without padding, e.g. a framebuffer
+/// without an alpha channel. Written pixels are composited over `background`, so only their color
+/// is kept. Reads are opaque, so painting blends over what the storage already shows.
+pub struct Rgb888Target<'a> {
+    bytes: &'a mut [u8],
+    width: usize,
+    height: usize,
+    background: Color32,
+}
+
+impl<'a> Rgb888Target<'a> {
+    /// Wraps `bytes` as `[width, height]` pixels.
+    ///
+    /// # Panics
+    /// If `bytes` holds fewer than `width * height * 3` bytes.
+    pub fn new(bytes: &'a mut [u8], [width, height]: [usize; 2], background: Color32) -> Self {
+        assert!(
+            bytes.len() >= width * height * 3,
+            "{} bytes are too few for {width}x{height} RGB pixels",
+            bytes.len()
+        );
+        Self {
+            bytes,
+            width,
+            height,
+            background,
+        }
+    }
+}
+
+impl RenderTarget for Rgb888Target<'_> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn get_pixel(&self, x: usize, y: usize) -> u32 {
+        let i = (y * self.width + x) * 3;
+        let [r, g, b] = [self.bytes[i], self.bytes[i + 1], self.bytes[i + 2]];
+        u32::from_le_bytes([r, g, b, 255])
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, pixel: u32) {
+        let i = (y * self.width + x) * 3;
+        self.bytes[i..i + 3].copy_from_slice(&composite_rgb(pixel, self.background));
+    }
+}
+
+/// The color of output pixel `pixel` composited over `background`.
+pub(crate) fn composite_rgb(pixel: u32, background: Color32) -> [u8; 3] {
+    let [r, g, b, a] = pixel.to_le_bytes();
+    match a {
+        255 => [r, g, b],
+        0 => [background.r(), background.g(), background.b()],
+        _ => {
+            let [r, g, b, _] =
+                pack_pixel(over(background.into(), unpack_pixel(pixel))).to_le_bytes();
+            [r, g, b]
+        }
+    }
+}
@@ -0,0 +1,167 @@
+//! Assertions for tests comparing painted frames, and a rich frame to compare.
+
+use std::io::{self, Write};
+
+use euc::{Buffer2d, Texture};
+
+use crate::{HeadlessInput, SoftwareGui};
+
+/// Environment variable naming a file to write an image of the differing pixels to when
+/// [`assert_buffer_matches`] fails.
+const DIFF_PATH_VAR: &str = "EGUI_EUC_DIFF_PPM";
+
+/// Panics unless every channel of every pixel of `actual` is within `tolerance` of `expected`.
+/// The message reports how many pixels differ, their bounding box, and the first of them, so a
+/// failing UI regression test points at what changed.
+///
+/// If the `EGUI_EUC_DIFF_PPM` environment variable is set, a failure also writes a PPM image to
+/// the path it names, with the differing pixels in red over a dimmed copy of `expected`.
+#[track_caller]
+pub fn assert_buffer_matches(actual: &Buffer2d<u32>, expected: &Buffer2d<u32>, tolerance: u8) {
+    assert_eq!(actual.size(), expected.size(), "buffers differ in size");
+
+    let [width, _] = actual.size();
+    let mut count = 0;
+    let mut bounds = [usize::MAX, usize::MAX, 0, 0];
+    let mut first = None;
+    let pixels = actual.raw().iter().zip(expected.raw());
+    for (i, (&actual, &expected)) in pixels.enumerate() {
+        if differs(actual, expected, tolerance) {
+            let (x, y) = (i % width, i / width);
+            count += 1;
+            bounds = [
+                bounds[0].min(x),
+                bounds[1].min(y),
+                bounds[2].max(x),
+                bounds[3].max(y),
+            ];
+            first.get_or_insert((x, y, actual, expected));
+        }
+    }
+
+    let Some((x, y, first_actual, first_expected)) = first else {
+        return;
+    };
+    let diff = match std::env::var_os(DIFF_PATH_VAR) {
+        Some(path) => {
+            let written = std::fs::File::create(&path).and_then(|file| {
+                let mut file = io::BufWriter::new(file);
+                write_diff_ppm(&mut file, actual, expected, tolerance)?;
+                file.flush()
+            });
+            match written {
+                Ok(()) => format!("; the diff is in {}", path.to_string_lossy()),
+                Err(err) => format!("; writing the diff failed: {err}"),
+            }
+        }
+        None => String::new(),
+    };
+    let [min_x, min_y, max_x, max_y] = bounds;
+    panic!(
+        "{count} pixels differ by more than {tolerance}, within [{min_x}, {min_y}] to \
+         [{max_x}, {max_y}]; the first, at [{x}, {y}], is {:?} instead of {:?}{diff}",
+        first_actual.to_le_bytes(),
+        first_expected.to_le_bytes(),
+    );
+}
+
+/// Renders the first frame of [`egui_demo_lib::DemoWindows`] through a [`SoftwareGui`] at
+/// `screen_size` pixels, as a common scene for reproducing rendering bugs. Input is fixed,
+/// including the time, so the same arguments always give the same pixels.
+pub fn render_demo(screen_size: [usize; 2], pixels_per_point: f32) -> Buffer2d<u32> {
+    let input = HeadlessInput::new(screen_size, pixels_per_point).build();
+    let mut demo = egui_demo_lib::DemoWindows::default();
+    SoftwareGui::new().paint(input, screen_size, |ctx| demo.ui(ctx))
+}
+
+fn differs(actual: u32, expected: u32, tolerance: u8) -> bool {
+    actual
+        .to_le_bytes()
+        .into_iter()
+        .zip(expected.to_le_bytes())
+        .any(|(a, e)| a.abs_diff(e) > tolerance)
+}
+
+/// Writes a binary PPM of the pixels of `actual` differing from `expected` by more than
+/// `tolerance`, in red, over `expected` at a quarter of its brightness. Alpha is dropped.
+fn write_diff_ppm(
+    out: &mut impl Write,
+    actual: &Buffer2d<u32>,
+    expected: &Buffer2d<u32>,
+    tolerance: u8,
+) -> io::Result<()> {
+    let [width, height] = actual.size();
+    write!(out, "P6\n{width} {height}\n255\n")?;
+    for (&actual, &expected) in actual.raw().iter().zip(expected.raw()) {
+        let [r, g, b, _] = expected.to_le_bytes();
+        let rgb = if differs(actual, expected, tolerance) {
+            [255, 0, 0]
+        } else {
+            [r / 4, g / 4, b / 4]
+        };
+        out.write_all(&rgb)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn buffer(pixels: &[u32]) -> Buffer2d<u32> {
+        let mut buffer = Buffer2d::fill([2, 2], 0);
+        buffer.raw_mut().copy_from_slice(pixels);
+        buffer
+    }
+
+    #[test]
+    fn identical_buffers_match() {
+        let pixels = [0xff00_00ff, 0x8040_2010, 0, u32::MAX];
+        assert_buffer_matches(&buffer(&pixels), &buffer(&pixels), 0);
+    }
+
+    #[test]
+    fn differences_within_tolerance_match() {
+        let actual = buffer(&[0x0303_0303, 0, 0, 0]);
+        let expected = buffer(&[0, 0, 0, 0x0303_0303]);
+        assert_buffer_matches(&actual, &expected, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "2 pixels differ by more than 1, within [0, 0] to [1, 1]; \
+                               the first, at [0, 0], is [2, 0, 0, 0] instead of [0, 0, 0, 0]")]
+    fn mismatches_are_reported() {
+        let actual = buffer(&[2, 0, 0, 0xff00_0000]);
+        let expected = buffer(&[0, 0, 0, 0]);
+        assert_buffer_matches(&actual, &expected, 1);
+    }
+
+    #[test]
+    fn demo_frame_has_content() {
+        let frame = render_demo([640, 480], 1.0);
+        assert_eq!(frame.size(), [640, 480]);
+
+        // A frame of the demo shows many colors; a blank or solid one means something is broken
+        let mut colors = frame.raw().to_vec();
+        colors.sort_unstable();
+        colors.dedup();
+        assert!(colors.len() > 16, "only {} colors", colors.len());
+
+        assert_buffer_matches(&render_demo([640, 480], 1.0), &frame, 0);
+    }
+
+    #[test]
+    fn diff_marks_differing_pixels() {
+        let actual = buffer(&[0, 0x00ff_ffff, 0, 0x0080_4020]);
+        let expected = buffer(&[0, 0, 0x0080_4020, 0x0080_4020]);
+        let mut ppm = Vec::new();
+        write_diff_ppm(&mut ppm, &actual, &expected, 0).unwrap();
+
+        let header = b"P6\n2 2\n255\n";
+        assert_eq!(&ppm[..header.len()], header);
+        assert_eq!(
+            &ppm[header.len()..],
+            [[0, 0, 0], [255, 0, 0], [255, 0, 0], [8, 16, 32]].concat()
+        );
+    }
+}
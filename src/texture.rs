@@ -0,0 +1,471 @@
+//! Storage for textures uploaded by egui.
+
+use egui::{epaint, Color32, Rgba, TextureOptions};
+use euc::{Buffer2d, Target, Texture};
+
+/// How a [`Painter`](crate::Painter) stores the pixels of uploaded textures.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextureStorage {
+    /// Linear premultiplied `Rgba`, 16 bytes per texel.
+    #[default]
+    Rgba,
+    /// Packed [`Color32`] bytes, 4 bytes per texel, decoded on every sample. See [`PackedTexture`].
+    Packed,
+    /// 4 bits per channel, 2 bytes per texel. See [`Rgba4444Texture`].
+    Rgba4444,
+    /// 5, 6 and 5 bits of color plus 8 bits of alpha, 3 bytes per texel. See [`Rgb565A8Texture`].
+    Rgb565A8,
+    /// Alpha only, 1 byte per texel, for white textures such as the font atlas. Color is dropped.
+    /// See [`Alpha8Texture`].
+    Alpha8,
+}
+
+/// A texture of [`Color32`]s (premultiplied sRGBA) packed into little-endian `u32`s, which reads
+/// as linear `Rgba`. Uses a quarter of the memory of a `Buffer2d<Rgba>`, at the cost of decoding each sample.
+pub struct PackedTexture(pub Buffer2d<u32>);
+
+impl Texture<2> for PackedTexture {
+    type Index = usize;
+    type Texel = Rgba;
+
+    fn size(&self) -> [Self::Index; 2] {
+        self.0.size()
+    }
+
+    fn read(&self, index: [Self::Index; 2]) -> Self::Texel {
+        let [r, g, b, a] = self.0.read(index).to_le_bytes();
+        Color32::from_rgba_premultiplied(r, g, b, a).into()
+    }
+}
+
+/// A texture of premultiplied sRGBA with 4 bits per channel, packed `0xRGBA` into `u16`s, which
+/// reads as linear `Rgba`. Uses half the memory of a [`PackedTexture`], with visible banding in
+/// smooth gradients.
+pub struct Rgba4444Texture(pub Buffer2d<u16>);
+
+impl Rgba4444Texture {
+    fn encode(color: Color32) -> u16 {
+        let [r, g, b, a] = color.to_array().map(|c| (c as u16 * 15 + 127) / 255);
+        (r << 12) | (g << 8) | (b << 4) | a
+    }
+}
+
+impl Texture<2> for Rgba4444Texture {
+    type Index = usize;
+    type Texel = Rgba;
+
+    fn size(&self) -> [Self::Index; 2] {
+        self.0.size()
+    }
+
+    fn read(&self, index: [Self::Index; 2]) -> Self::Texel {
+        let texel = self.0.read(index);
+        let [r, g, b, a] = [12, 8, 4, 0].map(|shift| ((texel >> shift) & 0xf) as u8 * 17);
+        Color32::from_rgba_premultiplied(r, g, b, a).into()
+    }
+}
+
+/// A texture of premultiplied sRGB packed 5:6:5 into `u16`s, with alpha in a separate plane of
+/// bytes, which reads as linear `Rgba`. Keeps smooth alpha, e.g. for anti-aliased edges, in
+/// three quarters of the memory of a [`PackedTexture`].
+pub struct Rgb565A8Texture {
+    pub color: Buffer2d<u16>,
+    pub alpha: Buffer2d<u8>,
+}
+
+impl Rgb565A8Texture {
+    fn write(&mut self, x: usize, y: usize, color: Color32) {
+        let [r, g, b, a] = color.to_array().map(u16::from);
+        let (r, g, b) = (
+            (r * 31 + 127) / 255,
+            (g * 63 + 127) / 255,
+            (b * 31 + 127) / 255,
+        );
+        self.color.write(x, y, (r << 11) | (g << 5) | b);
+        self.alpha.write(x, y, a as u8);
+    }
+}
+
+impl Texture<2> for Rgb565A8Texture {
+    type Index = usize;
+    type Texel = Rgba;
+
+    fn size(&self) -> [Self::Index; 2] {
+        self.alpha.size()
+    }
+
+    fn read(&self, index: [Self::Index; 2]) -> Self::Texel {
+        let texel = self.color.read(index);
+        let a = self.alpha.read(index);
+        let expand = |value: u16, max: u16| ((value * 255 + max / 2) / max) as u8;
+        // Rounding each channel separately can push color slightly above the alpha it was
+        // premultiplied with
+        let [r, g, b] = [
+            expand(texel >> 11, 31),
+            expand((texel >> 5) & 0x3f, 63),
+            expand(texel & 0x1f, 31),
+        ]
+        .map(|c| c.min(a));
+        Color32::from_rgba_premultiplied(r, g, b, a).into()
+    }
+}
+
+/// A texture of alpha bytes, which reads as premultiplied white of that alpha. Only suits textures
+/// whose color is white everywhere, like egui's font atlas, which it stores in a quarter of the
+/// memory of a [`PackedTexture`].
+pub struct Alpha8Texture(pub Buffer2d<u8>);
+
+impl Texture<2> for Alpha8Texture {
+    type Index = usize;
+    type Texel = Rgba;
+
+    fn size(&self) -> [Self::Index; 2] {
+        self.0.size()
+    }
+
+    fn read(&self, index: [Self::Index; 2]) -> Self::Texel {
+        let a = self.0.read(index);
+        Color32::from_rgba_premultiplied(a, a, a, a).into()
+    }
+}
+
+pub(crate) enum TexturePixels {
+    Rgba(Buffer2d<Rgba>),
+    Packed(PackedTexture),
+    Rgba4444(Rgba4444Texture),
+    Rgb565A8(Rgb565A8Texture),
+    Alpha8(Alpha8Texture),
+}
+
+impl TexturePixels {
+    pub(crate) fn size(&self) -> [usize; 2] {
+        match self {
+            TexturePixels::Rgba(pixels) => pixels.size(),
+            TexturePixels::Packed(pixels) => pixels.size(),
+            TexturePixels::Rgba4444(pixels) => pixels.size(),
+            TexturePixels::Rgb565A8(pixels) => pixels.size(),
+            TexturePixels::Alpha8(pixels) => pixels.size(),
+        }
+    }
+
+    pub(crate) fn read(&self, index: [usize; 2]) -> Rgba {
+        match self {
+            TexturePixels::Rgba(pixels) => pixels.read(index),
+            TexturePixels::Packed(pixels) => pixels.read(index),
+            TexturePixels::Rgba4444(pixels) => pixels.read(index),
+            TexturePixels::Rgb565A8(pixels) => pixels.read(index),
+            TexturePixels::Alpha8(pixels) => pixels.read(index),
+        }
+    }
+
+    fn write_color32(&mut self, x: usize, y: usize, color: Color32) {
+        match self {
+            TexturePixels::Rgba(pixels) => pixels.write(x, y, color.into()),
+            TexturePixels::Packed(pixels) => {
+                pixels.0.write(x, y, u32::from_le_bytes(color.to_array()))
+            }
+            TexturePixels::Rgba4444(pixels) => pixels.0.write(x, y, Rgba4444Texture::encode(color)),
+            TexturePixels::Rgb565A8(pixels) => pixels.write(x, y, color),
+            TexturePixels::Alpha8(pixels) => pixels.0.write(x, y, color.a()),
+        }
+    }
+
+    pub(crate) fn write_rgba(&mut self, x: usize, y: usize, color: Rgba) {
+        match self {
+            TexturePixels::Rgba(pixels) => pixels.write(x, y, color),
+            _ => self.write_color32(x, y, color.into()),
+        }
+    }
+}
+
+/// A whole texture delta with an empty image, see
+/// [`PainterError::EmptyImage`](crate::PainterError::EmptyImage).
+#[derive(Debug)]
+pub(crate) struct EmptyImage;
+
+pub(crate) struct SoftwareTexture {
+    pub(crate) pixels: TexturePixels,
+    pub(crate) options: egui::TextureOptions,
+    /// Color returned for UVs outside of `[0, 1]`, overriding the wrap mode, if set.
+    pub(crate) border: Option<Rgba>,
+    /// Size of the texture as egui sees it, which `pixels` may be downscaled from.
+    pub(crate) size: [usize; 2],
+    max_size: usize,
+    /// Whether uploaded [`Color32`]s hold linear rather than sRGB color.
+    linear: bool,
+}
+
+impl SoftwareTexture {
+    /// Creates a texture from `image`, downscaled so neither side of the stored pixels exceeds `max_size`.
+    /// If `linear`, the bytes of `image` and later updates are read as linear color.
+    ///
+    /// Fails if `image` is empty, since a texture without texels can't be sampled.
+    pub fn new(
+        image: &epaint::ImageData,
+        options: TextureOptions,
+        storage: TextureStorage,
+        max_size: usize,
+        linear: bool,
+    ) -> Result<Self, EmptyImage> {
+        let epaint::ImageData::Color(patch) = image;
+        if patch.width() == 0 || patch.height() == 0 {
+            return Err(EmptyImage);
+        }
+
+        let mut inst = Self::blank(patch.size, options, storage, max_size, linear);
+        // Decoded straight from the borrowed image into the new pixels
+        inst.write_patch(patch, [0, 0]);
+        Ok(inst)
+    }
+
+    /// Creates a transparent texture of `size`, like [`SoftwareTexture::new`] without decoding an
+    /// image, for uploads which arrive in pieces.
+    pub(crate) fn blank(
+        size: [usize; 2],
+        options: TextureOptions,
+        storage: TextureStorage,
+        max_size: usize,
+        linear: bool,
+    ) -> Self {
+        let stored_size = size.map(|side| side.min(max_size.max(1)));
+        let pixels = match storage {
+            TextureStorage::Rgba => {
+                TexturePixels::Rgba(Buffer2d::fill(stored_size, Rgba::TRANSPARENT))
+            }
+            TextureStorage::Packed => {
+                TexturePixels::Packed(PackedTexture(Buffer2d::fill(stored_size, 0)))
+            }
+            TextureStorage::Rgba4444 => {
+                TexturePixels::Rgba4444(Rgba4444Texture(Buffer2d::fill(stored_size, 0)))
+            }
+            TextureStorage::Rgb565A8 => TexturePixels::Rgb565A8(Rgb565A8Texture {
+                color: Buffer2d::fill(stored_size, 0),
+                alpha: Buffer2d::fill(stored_size, 0),
+            }),
+            TextureStorage::Alpha8 => {
+                TexturePixels::Alpha8(Alpha8Texture(Buffer2d::fill(stored_size, 0)))
+            }
+        };
+
+        Self {
+            pixels,
+            options,
+            border: None,
+            size,
+            max_size,
+            linear,
+        }
+    }
+
+    /// Wraps already decoded pixels, which are kept exactly as given. Values outside of `[0, 1]`
+    /// survive sampling, tinting and blending, and are only clamped when written to the output.
+    pub fn from_pixels(pixels: Buffer2d<Rgba>, options: TextureOptions) -> Self {
+        Self {
+            size: pixels.size(),
+            pixels: TexturePixels::Rgba(pixels),
+            options,
+            border: None,
+            max_size: usize::MAX,
+            linear: false,
+        }
+    }
+
+    /// Converts an uploaded texel to linear premultiplied color.
+    fn decode(&self, color: Color32) -> Rgba {
+        if self.linear {
+            let [r, g, b, a] = color.to_array().map(|c| c as f32 / 255.0);
+            Rgba::from_rgba_premultiplied(r, g, b, a)
+        } else {
+            color.into()
+        }
+    }
+
+    /// Sets whether uploads from now on hold linear color, see [`SoftwareTexture::new`].
+    pub fn set_linear(&mut self, linear: bool) {
+        self.linear = linear;
+    }
+
+    /// Whether the texture is stored as [`SoftwareTexture::new`] would store it with these
+    /// settings, so a whole upload can be written into it rather than recreate it.
+    pub(crate) fn is_stored_as(
+        &self,
+        storage: TextureStorage,
+        max_size: usize,
+        linear: bool,
+    ) -> bool {
+        self.storage() == storage && self.max_size == max_size && self.linear == linear
+    }
+
+    pub(crate) fn storage(&self) -> TextureStorage {
+        match self.pixels {
+            TexturePixels::Rgba(_) => TextureStorage::Rgba,
+            TexturePixels::Packed(_) => TextureStorage::Packed,
+            TexturePixels::Rgba4444(_) => TextureStorage::Rgba4444,
+            TexturePixels::Rgb565A8(_) => TextureStorage::Rgb565A8,
+            TexturePixels::Alpha8(_) => TextureStorage::Alpha8,
+        }
+    }
+
+    /// Applies `delta`. A partial delta with an empty image only changes the options.
+    ///
+    /// Fails, leaving the texture untouched, if `delta` replaces the whole texture with an empty
+    /// image.
+    pub fn update(&mut self, delta: &epaint::ImageDelta) -> Result<(), EmptyImage> {
+        let epaint::ImageData::Color(patch) = &delta.image;
+
+        if patch.width() == 0 || patch.height() == 0 {
+            if delta.is_whole() {
+                return Err(EmptyImage);
+            }
+            self.options = delta.options;
+            return Ok(());
+        }
+
+        if delta.is_whole() && patch.size != self.size {
+            let border = self.border;
+            *self = Self::new(
+                &delta.image,
+                delta.options,
+                self.storage(),
+                self.max_size,
+                self.linear,
+            )?;
+            self.border = border;
+            return Ok(());
+        }
+
+        self.options = delta.options;
+        self.write_patch(patch, delta.pos.unwrap_or([0, 0]));
+        Ok(())
+    }
+
+    /// Writes `patch` into the texture with its top-left corner at `[off_x, off_y]`.
+    fn write_patch(&mut self, patch: &egui::ColorImage, [off_x, off_y]: [usize; 2]) {
+        if self.pixels.size() == self.size {
+            for y in 0..patch.height() {
+                for x in 0..patch.width() {
+                    // Deltas whose size overstates their pixels are drawn as far as they go
+                    let Some(color) = patch_pixel(patch, x, y) else {
+                        continue;
+                    };
+                    let (x, y) = (x + off_x, y + off_y);
+                    if self.linear {
+                        let color = self.decode(color);
+                        self.pixels.write_rgba(x, y, color);
+                    } else {
+                        self.pixels.write_color32(x, y, color);
+                    }
+                }
+            }
+        } else {
+            self.update_downscaled(patch, [off_x, off_y]);
+        }
+    }
+
+    /// Copies the `[x, y, width, height]` rectangle `src_rect` of `src` into the texture, with its
+    /// top-left corner at `dst_pos`. Pixels are kept exactly as given, unless the texture is stored
+    /// downscaled or packed.
+    ///
+    /// # Panics
+    /// If either rectangle exceeds the bounds of its image.
+    pub fn update_region(
+        &mut self,
+        src: &Buffer2d<Rgba>,
+        [src_x, src_y, width, height]: [usize; 4],
+        [dst_x, dst_y]: [usize; 2],
+    ) {
+        if self.pixels.size() == self.size {
+            for y in 0..height {
+                for x in 0..width {
+                    self.pixels
+                        .write_rgba(dst_x + x, dst_y + y, src.read([src_x + x, src_y + y]));
+                }
+            }
+        } else {
+            let pixels = (0..height)
+                .flat_map(|y| (0..width).map(move |x| [src_x + x, src_y + y]))
+                .map(|pos| src.read(pos).into())
+                .collect();
+            let patch = egui::ColorImage::new([width, height], pixels);
+            self.update_downscaled(&patch, [dst_x, dst_y]);
+        }
+    }
+
+    /// Box filters `patch`, placed at `offset` in the texture, into the downscaled stored pixels.
+    /// Stored texels only partially covered by the patch are set to the average of the covered part.
+    fn update_downscaled(&mut self, patch: &egui::ColorImage, [off_x, off_y]: [usize; 2]) {
+        let [width, height] = self.size;
+        let [stored_width, stored_height] = self.pixels.size();
+
+        // Range of source pixels along one axis which a stored texel covers
+        let source_range = |texel: usize, size: usize, stored_size: usize| {
+            let start = texel * size / stored_size;
+            let end = ((texel + 1) * size).div_ceil(stored_size);
+            start..end
+        };
+
+        let first_x = off_x * stored_width / width;
+        let first_y = off_y * stored_height / height;
+        let last_x = ((off_x + patch.width()) * stored_width).div_ceil(width);
+        let last_y = ((off_y + patch.height()) * stored_height).div_ceil(height);
+
+        for stored_y in first_y..last_y.min(stored_height) {
+            for stored_x in first_x..last_x.min(stored_width) {
+                let mut sum = Rgba::TRANSPARENT;
+                let mut count = 0;
+                for y in source_range(stored_y, height, stored_height) {
+                    for x in source_range(stored_x, width, stored_width) {
+                        let in_patch = (off_x..off_x + patch.width()).contains(&x)
+                            && (off_y..off_y + patch.height()).contains(&y);
+                        let color = in_patch
+                            .then(|| patch_pixel(patch, x - off_x, y - off_y))
+                            .flatten();
+                        if let Some(color) = color {
+                            sum = sum + self.decode(color);
+                            count += 1;
+                        }
+                    }
+                }
+
+                if count > 0 {
+                    self.pixels
+                        .write_rgba(stored_x, stored_y, sum * (1.0 / count as f32));
+                }
+            }
+        }
+    }
+}
+
+/// Splits `delta` into two partial deltas, of its first `rows` rows and of the rest. Rows beyond
+/// the pixels of the image come out transparent.
+pub(crate) fn split_delta_rows(
+    delta: &epaint::ImageDelta,
+    rows: usize,
+) -> (epaint::ImageDelta, epaint::ImageDelta) {
+    let epaint::ImageData::Color(patch) = &delta.image;
+    let [x, y] = delta.pos.unwrap_or([0, 0]);
+    let width = patch.width();
+    let rows = rows.min(patch.height());
+
+    let image = |range: std::ops::Range<usize>| {
+        let mut pixels = patch
+            .pixels
+            .get(range.start * width..(range.end * width).min(patch.pixels.len()))
+            .unwrap_or_default()
+            .to_vec();
+        pixels.resize(range.len() * width, Color32::TRANSPARENT);
+        egui::ColorImage::new([width, range.len()], pixels)
+    };
+    (
+        epaint::ImageDelta::partial([x, y], image(0..rows), delta.options),
+        epaint::ImageDelta::partial([x, y + rows], image(rows..patch.height()), delta.options),
+    )
+}
+
+/// The pixel at `(x, y)` of `patch`, or `None` if it is outside of the image or its pixels.
+fn patch_pixel(patch: &egui::ColorImage, x: usize, y: usize) -> Option<Color32> {
+    if x >= patch.width() {
+        return None;
+    }
+    patch.pixels.get(y * patch.width() + x).copied()
+}
@@ -0,0 +1,81 @@
+//! 2D affine transforms of logical positions, see
+//! [`Painter::set_transform_around`](crate::Painter::set_transform_around).
+
+use egui::{Pos2, Rect, Vec2};
+
+/// A 2D affine transform of positions in points: `matrix * pos + translation`, with `matrix`
+/// given as its rows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Affine2 {
+    pub matrix: [[f32; 2]; 2],
+    pub translation: Vec2,
+}
+
+impl Affine2 {
+    pub const IDENTITY: Self = Self {
+        matrix: [[1.0, 0.0], [0.0, 1.0]],
+        translation: Vec2::ZERO,
+    };
+
+    /// Rotates by `rotation` radians (clockwise on screen, as y points down) and scales by `scale`,
+    /// both around `pivot`, which stays in place.
+    pub fn around(pivot: Pos2, rotation: f32, scale: f32) -> Self {
+        let (sin, cos) = rotation.sin_cos();
+        let matrix = [[cos * scale, -sin * scale], [sin * scale, cos * scale]];
+        let rotated = Self {
+            matrix,
+            translation: Vec2::ZERO,
+        }
+        .apply(pivot);
+        Self {
+            matrix,
+            translation: pivot - rotated,
+        }
+    }
+
+    /// This transform followed by a translation by `offset`.
+    pub fn then_translate(self, offset: Vec2) -> Self {
+        Self {
+            translation: self.translation + offset,
+            ..self
+        }
+    }
+
+    /// This transform followed by mirroring within the rectangle from the origin to `size`, along
+    /// x if `horizontal` and along y if `vertical`.
+    pub fn then_mirror(self, [horizontal, vertical]: [bool; 2], size: Vec2) -> Self {
+        let [[a, b], [c, d]] = self.matrix;
+        let (mut matrix, mut translation) = (self.matrix, self.translation);
+        if horizontal {
+            matrix[0] = [-a, -b];
+            translation.x = size.x - translation.x;
+        }
+        if vertical {
+            matrix[1] = [-c, -d];
+            translation.y = size.y - translation.y;
+        }
+        Self {
+            matrix,
+            translation,
+        }
+    }
+
+    pub fn apply(&self, pos: Pos2) -> Pos2 {
+        let [[a, b], [c, d]] = self.matrix;
+        Pos2::new(a * pos.x + b * pos.y, c * pos.x + d * pos.y) + self.translation
+    }
+
+    /// The axis-aligned bounding box of `rect` once transformed. Exact unless the transform rotates.
+    pub fn apply_rect(&self, rect: Rect) -> Rect {
+        if !rect.is_finite() {
+            // Unbounded clip rects stay unbounded
+            return rect.translate(self.translation);
+        }
+        Rect::from_points(&[
+            self.apply(rect.left_top()),
+            self.apply(rect.right_top()),
+            self.apply(rect.left_bottom()),
+            self.apply(rect.right_bottom()),
+        ])
+    }
+}
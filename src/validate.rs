@@ -0,0 +1,62 @@
+//! Checks of meshes from untrusted sources, before they reach the rasterizer.
+
+use std::fmt;
+
+use egui::epaint;
+
+/// Why a mesh can't be drawn, see [`validate_mesh`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MeshError {
+    /// The index at `position` in the index list refers to a vertex past the end of the mesh.
+    IndexOutOfRange {
+        position: usize,
+        index: u32,
+        vertex_count: usize,
+    },
+    /// The number of indices isn't a multiple of 3, so the last triangle is incomplete.
+    IncompleteTriangle { index_count: usize },
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MeshError::IndexOutOfRange {
+                position,
+                index,
+                vertex_count,
+            } => write!(
+                f,
+                "index {index} at position {position} is out of range for {vertex_count} vertices"
+            ),
+            MeshError::IncompleteTriangle { index_count } => {
+                write!(f, "{index_count} indices don't make up whole triangles")
+            }
+        }
+    }
+}
+
+impl std::error::Error for MeshError {}
+
+/// Checks that every index of `mesh` refers to one of its vertices, and that its indices make up
+/// whole triangles. [`Painter`](crate::Painter) skips meshes failing this instead of panicking.
+pub fn validate_mesh(mesh: &epaint::Mesh) -> Result<(), MeshError> {
+    if mesh.indices.len() % 3 != 0 {
+        return Err(MeshError::IncompleteTriangle {
+            index_count: mesh.indices.len(),
+        });
+    }
+
+    let vertex_count = mesh.vertices.len();
+    match mesh
+        .indices
+        .iter()
+        .position(|&index| index as usize >= vertex_count)
+    {
+        Some(position) => Err(MeshError::IndexOutOfRange {
+            position,
+            index: mesh.indices[position],
+            vertex_count,
+        }),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,18 @@
+//! Output helpers for drawing frames to an HTML `<canvas>`.
+
+use euc::{Buffer2d, Texture};
+use wasm_bindgen::{Clamped, JsValue};
+
+/// Converts a painted buffer into [`web_sys::ImageData`], ready for
+/// `CanvasRenderingContext2d::put_image_data`.
+///
+/// Output pixels are already straight-alpha RGBA8, the layout canvases expect, so this is a single copy.
+pub fn buffer_to_web_image_data(buffer: &Buffer2d<u32>) -> Result<web_sys::ImageData, JsValue> {
+    let [width, height] = buffer.size();
+    let bytes: &[u8] = bytemuck::cast_slice(buffer.raw());
+    web_sys::ImageData::new_with_u8_clamped_array_and_sh(
+        Clamped(bytes),
+        width as u32,
+        height as u32,
+    )
+}
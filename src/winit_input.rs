@@ -0,0 +1,223 @@
+//! Translation of winit window events into the [`egui::RawInput`] of a [`SoftwareGui`](crate::SoftwareGui).
+
+use egui::{Event, Key, Modifiers, MouseWheelUnit, PointerButton, Pos2, RawInput, Rect, Vec2};
+use winit::dpi::PhysicalSize;
+use winit::event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent};
+use winit::keyboard::{Key as WinitKey, ModifiersState, NamedKey};
+
+/// Accumulates winit [`WindowEvent`]s into a [`RawInput`] for the next frame.
+///
+/// Everything is sized in physical pixels, since that is what gets painted: the screen rect is the
+/// window's inner size divided by the window's scale factor, which becomes `pixels_per_point`.
+pub struct WinitInput {
+    raw_input: RawInput,
+    size: [usize; 2],
+    pixels_per_point: f32,
+    /// Last pointer position, in points.
+    pointer_pos: Option<Pos2>,
+    modifiers: Modifiers,
+    start_time: std::time::Instant,
+}
+
+impl WinitInput {
+    /// Starts tracking a window of `size` physical pixels with the given scale factor.
+    pub fn new(size: PhysicalSize<u32>, scale_factor: f64) -> Self {
+        Self {
+            raw_input: RawInput::default(),
+            size: [size.width as usize, size.height as usize],
+            pixels_per_point: scale_factor as f32,
+            pointer_pos: None,
+            modifiers: Modifiers::default(),
+            start_time: std::time::Instant::now(),
+        }
+    }
+
+    /// Size of the window in physical pixels, i.e. the size to paint at.
+    pub fn screen_size(&self) -> [usize; 2] {
+        self.size
+    }
+
+    pub fn pixels_per_point(&self) -> f32 {
+        self.pixels_per_point
+    }
+
+    /// Records `event`. Events egui has no use for are ignored.
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::Resized(size) => {
+                self.size = [size.width as usize, size.height as usize];
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                self.pixels_per_point = *scale_factor as f32;
+            }
+            WindowEvent::Focused(focused) => {
+                self.raw_input.focused = *focused;
+                self.raw_input.events.push(Event::WindowFocused(*focused));
+            }
+            WindowEvent::ModifiersChanged(modifiers) => {
+                self.modifiers = egui_modifiers(modifiers.state());
+                self.raw_input.modifiers = self.modifiers;
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                let pos = Pos2::new(position.x as f32, position.y as f32) / self.pixels_per_point;
+                self.pointer_pos = Some(pos);
+                self.raw_input.events.push(Event::PointerMoved(pos));
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.pointer_pos = None;
+                self.raw_input.events.push(Event::PointerGone);
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let (Some(pos), Some(button)) = (self.pointer_pos, pointer_button(*button)) else {
+                    return;
+                };
+                self.raw_input.events.push(Event::PointerButton {
+                    pos,
+                    button,
+                    pressed: *state == ElementState::Pressed,
+                    modifiers: self.modifiers,
+                });
+            }
+            WindowEvent::MouseWheel { delta, .. } => {
+                let (unit, delta) = match *delta {
+                    MouseScrollDelta::LineDelta(x, y) => (MouseWheelUnit::Line, Vec2::new(x, y)),
+                    MouseScrollDelta::PixelDelta(delta) => (
+                        MouseWheelUnit::Point,
+                        Vec2::new(delta.x as f32, delta.y as f32) / self.pixels_per_point,
+                    ),
+                };
+                self.raw_input.events.push(Event::MouseWheel {
+                    unit,
+                    delta,
+                    modifiers: self.modifiers,
+                });
+            }
+            WindowEvent::KeyboardInput { event, .. } => {
+                let pressed = event.state == ElementState::Pressed;
+                if let Some(key) = key(&event.logical_key) {
+                    self.raw_input.events.push(Event::Key {
+                        key,
+                        physical_key: None,
+                        pressed,
+                        repeat: event.repeat,
+                        modifiers: self.modifiers,
+                    });
+                }
+
+                // Shortcuts and control characters are not text
+                let text = event.text.as_deref().filter(|text| {
+                    pressed && !self.modifiers.command && !text.chars().any(|c| c.is_control())
+                });
+                if let Some(text) = text {
+                    self.raw_input.events.push(Event::Text(text.to_owned()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Takes the input gathered since the last call, with the screen rect, scale and time filled in.
+    pub fn take_raw_input(&mut self) -> RawInput {
+        let [width, height] = self.size;
+        self.raw_input.screen_rect = Some(Rect::from_min_size(
+            Pos2::ZERO,
+            Vec2::new(width as f32, height as f32) / self.pixels_per_point,
+        ));
+        self.raw_input
+            .viewports
+            .entry(self.raw_input.viewport_id)
+            .or_default()
+            .native_pixels_per_point = Some(self.pixels_per_point);
+        self.raw_input.time = Some(self.start_time.elapsed().as_secs_f64());
+        self.raw_input.take()
+    }
+}
+
+/// The modifiers egui sees for winit's `state`. Shortcuts use Cmd (the super key) on macOS and
+/// Ctrl elsewhere, which egui reads from `command`.
+fn egui_modifiers(state: ModifiersState) -> Modifiers {
+    let mac = cfg!(target_os = "macos");
+    Modifiers {
+        alt: state.alt_key(),
+        ctrl: state.control_key(),
+        shift: state.shift_key(),
+        mac_cmd: mac && state.super_key(),
+        command: if mac {
+            state.super_key()
+        } else {
+            state.control_key()
+        },
+    }
+}
+
+fn pointer_button(button: MouseButton) -> Option<PointerButton> {
+    match button {
+        MouseButton::Left => Some(PointerButton::Primary),
+        MouseButton::Right => Some(PointerButton::Secondary),
+        MouseButton::Middle => Some(PointerButton::Middle),
+        MouseButton::Back => Some(PointerButton::Extra1),
+        MouseButton::Forward => Some(PointerButton::Extra2),
+        MouseButton::Other(_) => None,
+    }
+}
+
+fn key(key: &WinitKey) -> Option<Key> {
+    match key {
+        WinitKey::Named(named) => Some(match named {
+            NamedKey::ArrowDown => Key::ArrowDown,
+            NamedKey::ArrowLeft => Key::ArrowLeft,
+            NamedKey::ArrowRight => Key::ArrowRight,
+            NamedKey::ArrowUp => Key::ArrowUp,
+            NamedKey::Escape => Key::Escape,
+            NamedKey::Tab => Key::Tab,
+            NamedKey::Backspace => Key::Backspace,
+            NamedKey::Enter => Key::Enter,
+            NamedKey::Space => Key::Space,
+            NamedKey::Insert => Key::Insert,
+            NamedKey::Delete => Key::Delete,
+            NamedKey::Home => Key::Home,
+            NamedKey::End => Key::End,
+            NamedKey::PageUp => Key::PageUp,
+            NamedKey::PageDown => Key::PageDown,
+            _ => return None,
+        }),
+        WinitKey::Character(text) => Key::from_name(text),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_follows_the_platform_shortcut_key() {
+        let ctrl = egui_modifiers(ModifiersState::CONTROL);
+        let cmd = egui_modifiers(ModifiersState::SUPER);
+        assert!(ctrl.ctrl && !cmd.ctrl);
+        if cfg!(target_os = "macos") {
+            assert!(!ctrl.command && !ctrl.mac_cmd);
+            assert!(cmd.command && cmd.mac_cmd);
+        } else {
+            assert!(ctrl.command && !ctrl.mac_cmd);
+            assert!(!cmd.command && !cmd.mac_cmd);
+        }
+    }
+
+    #[test]
+    fn shortcuts_are_not_text() {
+        let mut input = WinitInput::new(PhysicalSize::new(100, 100), 1.0);
+        let shortcut = if cfg!(target_os = "macos") {
+            ModifiersState::SUPER
+        } else {
+            ModifiersState::CONTROL
+        };
+        input.handle_event(&WindowEvent::ModifiersChanged(shortcut.into()));
+        let raw_input = input.take_raw_input();
+        assert!(raw_input.modifiers.command);
+        assert_eq!(
+            raw_input.screen_rect.map(|rect| rect.size()),
+            Some(Vec2::splat(100.0))
+        );
+    }
+}
@@ -0,0 +1,164 @@
+//! Steady-state painting must not keep allocating, and `Painter::shrink_to_fit` must give memory
+//! back. Kept in its own test binary, since the allocator counts every thread of the process, and
+//! the tests take turns for the same reason.
+
+mod common;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicIsize, Ordering};
+use std::sync::Mutex;
+
+use common::{scene, white_texture, SCREEN_SIZE};
+use egui::{Rgba, TextureId, TexturesDelta};
+use egui_euc::Painter;
+use euc::Buffer2d;
+
+/// Bytes currently allocated through [`Counting`].
+static LIVE_BYTES: AtomicIsize = AtomicIsize::new(0);
+/// Most bytes allocated at once, for catching temporary copies.
+static PEAK_BYTES: AtomicIsize = AtomicIsize::new(0);
+
+fn record_peak(live: isize) {
+    PEAK_BYTES.fetch_max(live, Ordering::Relaxed);
+}
+
+struct Counting;
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let size = layout.size() as isize;
+        record_peak(LIVE_BYTES.fetch_add(size, Ordering::Relaxed) + size);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE_BYTES.fetch_sub(layout.size() as isize, Ordering::Relaxed);
+        unsafe { System.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let growth = new_size as isize - layout.size() as isize;
+        record_peak(LIVE_BYTES.fetch_add(growth, Ordering::Relaxed) + growth);
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: Counting = Counting;
+
+/// Held by each test while it counts.
+static COUNTING: Mutex<()> = Mutex::new(());
+
+#[test]
+fn steady_state_painting_keeps_no_allocations() {
+    let _counting = COUNTING.lock().unwrap();
+    let primitives = scene();
+    let mut painter = Painter::new();
+    // Culling uses the most scratch space
+    painter.set_occlusion_culling(true);
+    painter.paint_with(&white_texture(), &primitives, 1.0, SCREEN_SIZE, |_| ());
+
+    let empty = TexturesDelta::default();
+    let before = LIVE_BYTES.load(Ordering::Relaxed);
+    for _ in 0..10 {
+        painter.paint_with(&empty, &primitives, 1.0, SCREEN_SIZE, |_| ());
+    }
+    let after = LIVE_BYTES.load(Ordering::Relaxed);
+    assert_eq!(
+        after - before,
+        0,
+        "painting kept {} more bytes",
+        after - before
+    );
+}
+
+#[test]
+fn painting_keeps_no_depth_buffer() {
+    let _counting = COUNTING.lock().unwrap();
+    let size = [512, 512];
+    let color_bytes = (size[0] * size[1] * std::mem::size_of::<u32>()) as isize;
+    let mut painter = Painter::new();
+
+    let before = LIVE_BYTES.load(Ordering::Relaxed);
+    painter.paint_with(&white_texture(), &scene(), 1.0, size, |_| ());
+    let kept = LIVE_BYTES.load(Ordering::Relaxed) - before;
+    // The reused color buffer, but no depth buffer of the same size next to it
+    assert!(
+        kept >= color_bytes && kept < color_bytes * 3 / 2,
+        "kept {kept} bytes for {color_bytes} bytes of color"
+    );
+}
+
+#[test]
+fn uploads_do_not_copy_the_image() {
+    let _counting = COUNTING.lock().unwrap();
+    let size = [256, 256];
+    let image = egui::ColorImage::new(size, vec![egui::Color32::RED; size[0] * size[1]]);
+    let image_bytes = (size[0] * size[1] * std::mem::size_of::<egui::Color32>()) as isize;
+    let textures_delta = TexturesDelta {
+        set: vec![(
+            TextureId::User(1),
+            egui::epaint::ImageDelta::full(image, Default::default()),
+        )],
+        free: Vec::new(),
+    };
+    let mut painter = Painter::new();
+    // Four bytes per texel, like the image
+    painter.set_texture_storage(egui_euc::TextureStorage::Packed);
+
+    let before = LIVE_BYTES.load(Ordering::Relaxed);
+    PEAK_BYTES.store(before, Ordering::Relaxed);
+    painter.paint_and_update_textures(&textures_delta, &[], 1.0, [1, 1]);
+    let peak = PEAK_BYTES.load(Ordering::Relaxed) - before;
+    // The stored texture, but no copy of the image on the way
+    assert!(
+        peak >= image_bytes && peak < image_bytes * 3 / 2,
+        "peaked at {peak} bytes for {image_bytes} bytes of texels"
+    );
+}
+
+#[test]
+fn presized_texture_tables_do_not_grow() {
+    let _counting = COUNTING.lock().unwrap();
+    let textures: Vec<_> = (0..100)
+        .map(|i| (TextureId::User(i), Buffer2d::fill([1, 1], Rgba::WHITE)))
+        .collect();
+    let mut painter = Painter::with_capacity(textures.len());
+
+    let before = LIVE_BYTES.load(Ordering::Relaxed);
+    for (id, pixels) in textures {
+        painter.register_user_texture(id, pixels, Default::default());
+    }
+    let after = LIVE_BYTES.load(Ordering::Relaxed);
+    // The pixels were allocated up front, so only growing the table would allocate
+    assert_eq!(
+        after - before,
+        0,
+        "registering allocated {} bytes",
+        after - before
+    );
+}
+
+#[test]
+fn shrinking_releases_the_texture_table() {
+    let _counting = COUNTING.lock().unwrap();
+    let ids: Vec<_> = (0..500).map(TextureId::User).collect();
+    let mut painter = Painter::new();
+    for &id in &ids {
+        painter.register_user_texture(id, Buffer2d::fill([1, 1], Rgba::WHITE), Default::default());
+    }
+    // A light screen after a heavy one
+    let frees = TexturesDelta {
+        set: Vec::new(),
+        free: ids,
+    };
+    painter.paint_and_update_textures(&frees, &[], 1.0, SCREEN_SIZE);
+
+    let before = LIVE_BYTES.load(Ordering::Relaxed);
+    painter.shrink_to_fit();
+    let after = LIVE_BYTES.load(Ordering::Relaxed);
+    assert!(
+        after < before,
+        "{after} bytes after shrinking, {before} before"
+    );
+}
@@ -0,0 +1,314 @@
+//! Helpers working on finished frames.
+
+mod common;
+
+use common::{rect, white_texture, SCREEN_SIZE};
+use egui::Color32;
+use egui_euc::{pack_pixel, unpack_pixel, BlendOp, Painter};
+use euc::{Buffer2d, Target, Texture};
+
+const BLACK: u32 = u32::from_le_bytes([0, 0, 0, 255]);
+const WHITE: u32 = u32::from_le_bytes([255, 255, 255, 255]);
+
+/// Coordinates of the pixels of `buffer` equal to `pixel`.
+fn positions_of(buffer: &Buffer2d<u32>, pixel: u32) -> Vec<[usize; 2]> {
+    let [width, height] = buffer.size();
+    (0..height)
+        .flat_map(|y| (0..width).map(move |x| [x, y]))
+        .filter(|&pos| buffer.read(pos) == pixel)
+        .collect()
+}
+
+#[test]
+fn sprites_are_clipped_to_the_buffer() {
+    // A white cursor with a transparent hotspot
+    let mut sprite = Buffer2d::fill([3, 3], WHITE);
+    sprite.write(1, 1, 0);
+
+    // Hanging over the top left corner
+    let mut dst = Buffer2d::fill([4, 4], BLACK);
+    egui_euc::draw_sprite_over(&mut dst, &sprite, [0, 0], [1, 1]);
+    assert_eq!(positions_of(&dst, WHITE), [[1, 0], [0, 1], [1, 1]]);
+    assert_eq!(positions_of(&dst, BLACK).len(), 13);
+
+    // Hanging over the bottom right corner
+    let mut dst = Buffer2d::fill([4, 4], BLACK);
+    egui_euc::draw_sprite_over(&mut dst, &sprite, [3, 3], [0, 0]);
+    assert_eq!(positions_of(&dst, WHITE), [[3, 3]]);
+
+    // Entirely outside
+    let mut dst = Buffer2d::fill([4, 4], BLACK);
+    egui_euc::draw_sprite_over(&mut dst, &sprite, [-5, 9], [0, 0]);
+    assert_eq!(positions_of(&dst, BLACK).len(), 16);
+}
+
+#[test]
+fn alpha_is_read_from_the_pixel_layout() {
+    let mut buffer = Buffer2d::fill([4, 2], 0);
+    buffer.write(1, 0, WHITE);
+    buffer.write(2, 1, u32::from_le_bytes([10, 20, 30, 128]));
+
+    assert_eq!(Painter::pixel_alpha_at(&buffer, 0, 0), 0);
+    assert_eq!(Painter::pixel_alpha_at(&buffer, 1, 0), 255);
+    assert_eq!(Painter::pixel_alpha_at(&buffer, 2, 1), 128);
+    // Outside of the buffer everything is see-through
+    assert_eq!(Painter::pixel_alpha_at(&buffer, 4, 0), 0);
+    assert_eq!(Painter::pixel_alpha_at(&buffer, 1, 2), 0);
+
+    assert!(Painter::is_opaque_at(&buffer, 1, 0));
+    assert!(!Painter::is_opaque_at(&buffer, 2, 1));
+    assert!(!Painter::is_opaque_at(&buffer, 0, 0));
+    assert!(!Painter::is_opaque_at(&buffer, 9, 9));
+}
+
+#[test]
+fn drawn_pixels_are_opaque() {
+    let primitives = [rect([8.0, 8.0], [16.0, 16.0], Color32::BLUE)];
+    let frame =
+        Painter::new().paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    assert!(Painter::is_opaque_at(&frame, 12, 12));
+    assert_eq!(Painter::pixel_alpha_at(&frame, 30, 30), 0);
+}
+
+#[test]
+fn coverage_is_the_alpha_of_the_color_buffer() {
+    let (color, coverage) = Painter::new().paint_color_and_coverage(
+        &white_texture(),
+        &common::scene(),
+        1.0,
+        SCREEN_SIZE,
+    );
+    assert_eq!(coverage.size(), SCREEN_SIZE);
+    let alphas: Vec<u8> = color.raw().iter().map(|p| p.to_le_bytes()[3]).collect();
+    assert!(coverage.raw() == alphas);
+
+    // Translucent shapes over nothing leave partial coverage
+    let translucent = [rect([0.0, 0.0], [8.0, 8.0], Color32::from_white_alpha(64))];
+    let (_, coverage) =
+        Painter::new().paint_color_and_coverage(&white_texture(), &translucent, 1.0, SCREEN_SIZE);
+    assert_eq!(coverage.read([4, 4]), 64);
+    assert_eq!(coverage.read([12, 4]), 0);
+}
+
+#[test]
+fn composite_stacks_layers_in_order() {
+    let background = Buffer2d::fill([4, 1], BLACK);
+    let middle = Buffer2d::fill([2, 1], u32::from_le_bytes([255, 255, 255, 128]));
+    let glow = u32::from_le_bytes([200, 40, 0, 255]);
+    // The last texel hangs off the right edge
+    let mut top = Buffer2d::fill([3, 1], glow);
+    top.write(2, 0, WHITE);
+
+    let output = egui_euc::composite(
+        [4, 1],
+        &[
+            (&background, BlendOp::Over, [0, 0]),
+            (&middle, BlendOp::Over, [1, 0]),
+            (&top, BlendOp::Add, [2, 0]),
+        ],
+    );
+
+    let grey =
+        pack_pixel(BlendOp::Over.blend(unpack_pixel(BLACK), unpack_pixel(middle.read([0, 0]))));
+    let [r, g, b, a] = grey.to_le_bytes();
+    assert!(
+        (185..=190).contains(&r) && r == g && g == b && a == 255,
+        "{r} {g} {b} {a}"
+    );
+    let add = |below: u32| pack_pixel(BlendOp::Add.blend(unpack_pixel(below), unpack_pixel(glow)));
+    assert_eq!(output.raw(), [BLACK, grey, add(grey), add(BLACK)]);
+}
+
+#[test]
+fn accumulated_frames_average_in_linear_space() {
+    let mut painter = Painter::new();
+    assert!(painter.resolve().is_none());
+
+    painter.accumulate(&Buffer2d::fill([2, 1], BLACK));
+    painter.accumulate(&Buffer2d::fill([2, 1], WHITE));
+    let average = painter.resolve().unwrap();
+    // Linear 0.5, not the sRGB midpoint of 128
+    let grey = pack_pixel(egui::Rgba::from_gray(0.5));
+    assert_eq!(average.raw(), [grey; 2]);
+
+    // A frame of another size starts over
+    painter.accumulate(&Buffer2d::fill([1, 1], WHITE));
+    assert_eq!(painter.resolve().unwrap().raw(), [WHITE]);
+
+    painter.clear_accumulation();
+    assert!(painter.resolve().is_none());
+}
+
+#[test]
+fn region_views_read_and_write_inside_their_region() {
+    let mut buffer = Buffer2d::fill([4, 4], BLACK);
+    let mut view = egui_euc::RegionView::new(&mut buffer, [1, 1, 2, 2]);
+    assert_eq!(view.size(), [2, 2]);
+    view.write(0, 0, WHITE);
+    view.write(1, 1, WHITE);
+    assert_eq!(view.read([1, 1]), WHITE);
+    assert_eq!(view.read([0, 1]), BLACK);
+
+    view.into_inner();
+    assert_eq!(positions_of(&buffer, WHITE), [[1, 1], [2, 2]]);
+}
+
+#[test]
+#[should_panic = "Read outside of region"]
+fn region_views_reject_reads_outside() {
+    let buffer = Buffer2d::fill([4, 4], BLACK);
+    // Inside of the buffer, but not of the region
+    egui_euc::RegionView::new(&buffer, [1, 1, 2, 2]).read([2, 0]);
+}
+
+#[test]
+#[should_panic = "Region exceeds the bounds of the target"]
+fn region_views_must_fit_the_target() {
+    let buffer = Buffer2d::fill([4, 4], BLACK);
+    egui_euc::RegionView::new(&buffer, [3, 0, 2, 2]);
+}
+
+#[test]
+fn lines_cover_their_width_and_smooth_their_edges() {
+    // Two pixels wide, on the boundary between rows 4 and 5
+    let mut dst = Buffer2d::fill([20, 10], BLACK);
+    egui_euc::draw_line_aa(
+        &mut dst,
+        egui::pos2(2.0, 5.0),
+        egui::pos2(18.0, 5.0),
+        2.0,
+        Color32::WHITE,
+    );
+    let expected: Vec<_> = (4..6).flat_map(|y| (2..18).map(move |x| [x, y])).collect();
+    assert_eq!(positions_of(&dst, WHITE), expected);
+    assert_eq!(positions_of(&dst, BLACK).len(), 20 * 10 - expected.len());
+
+    // Diagonal edges are partially covered
+    let mut dst = Buffer2d::fill([20, 10], BLACK);
+    egui_euc::draw_line_aa(
+        &mut dst,
+        egui::pos2(2.0, 1.0),
+        egui::pos2(18.0, 9.0),
+        2.0,
+        Color32::WHITE,
+    );
+    assert!(dst
+        .raw()
+        .iter()
+        .any(|&pixel| pixel != WHITE && pixel != BLACK));
+}
+
+#[test]
+fn aligned_rows_are_padded_with_zeros() {
+    let mut buffer = Buffer2d::fill([3, 2], WHITE);
+    buffer.write(0, 1, BLACK);
+    let (bytes, stride) = egui_euc::buffer_to_aligned_bytes(&buffer, 16);
+    assert_eq!(stride, 16);
+    let rows: Vec<&[u8]> = bytes.chunks(stride).collect();
+    assert_eq!(rows[0], [[255; 4], [255; 4], [255; 4], [0; 4]].concat());
+    assert_eq!(
+        rows[1],
+        [[0, 0, 0, 255], [255; 4], [255; 4], [0; 4]].concat()
+    );
+
+    // Already aligned rows, and no alignment at all, aren't padded
+    assert_eq!(egui_euc::buffer_to_aligned_bytes(&buffer, 4).1, 12);
+    assert_eq!(egui_euc::buffer_to_aligned_bytes(&buffer, 0).1, 12);
+    let empty = Buffer2d::fill([0, 2], WHITE);
+    assert_eq!(
+        egui_euc::buffer_to_aligned_bytes(&empty, 16),
+        (Vec::new(), 0)
+    );
+}
+
+#[test]
+fn clearing_the_interior_keeps_the_border() {
+    let mut buffer = Buffer2d::fill([5, 4], WHITE);
+    egui_euc::clear_interior(&mut buffer, 1);
+    assert_eq!(
+        positions_of(&buffer, 0),
+        [[1, 1], [2, 1], [3, 1], [1, 2], [2, 2], [3, 2]]
+    );
+
+    // Borders meeting in the middle leave nothing to clear
+    let mut buffer = Buffer2d::fill([5, 4], WHITE);
+    egui_euc::clear_interior(&mut buffer, 2);
+    assert!(positions_of(&buffer, 0).is_empty());
+    egui_euc::clear_interior(&mut buffer, 10);
+    assert!(positions_of(&buffer, 0).is_empty());
+}
+
+#[test]
+fn feathered_scissors_ramp_alpha_towards_their_edges() {
+    let mut buffer = Buffer2d::fill([10, 9], 0);
+    // Row 4 is far enough from the top and bottom edges to only fade horizontally
+    let mut scissor = egui_euc::FeatheredScissor::new(&mut buffer, [1, 0, 8, 9], 4.0);
+    for x in 0..10 {
+        scissor.write(x, 4, WHITE);
+    }
+    let alpha: Vec<u8> = (0..10)
+        .map(|x| buffer.read([x, 4]).to_le_bytes()[3])
+        .collect();
+    // Coverage is the distance of the pixel center from the nearest edge, over the feather
+    let expected = [
+        0.0, 0.125, 0.375, 0.625, 0.875, 0.875, 0.625, 0.375, 0.125, 0.0,
+    ];
+    for (x, (&alpha, coverage)) in alpha.iter().zip(expected).enumerate() {
+        let expected = coverage * 255.0;
+        assert!(
+            (alpha as f32 - expected).abs() <= 1.0,
+            "alpha {alpha} at x {x}, expected {expected}"
+        );
+    }
+
+    // Without a feather only the pixels outside are cut
+    let mut buffer = Buffer2d::fill([10, 9], 0);
+    let mut scissor = egui_euc::FeatheredScissor::new(&mut buffer, [1, 0, 8, 9], 0.0);
+    for x in 0..10 {
+        scissor.write(x, 4, WHITE);
+    }
+    let drawn: Vec<usize> = (0..10).filter(|&x| buffer.read([x, 4]) == WHITE).collect();
+    assert_eq!(drawn, (1..9).collect::<Vec<_>>());
+}
+
+#[test]
+fn planar_output_splits_color_and_alpha() {
+    let mut buffer = Buffer2d::fill([2, 1], WHITE);
+    buffer.write(1, 0, u32::from_le_bytes([10, 20, 30, 40]));
+    let (rgb, alpha) = egui_euc::buffer_to_planar(&buffer);
+    // Color stays straight, not multiplied by alpha
+    assert_eq!(rgb, [255, 255, 255, 10, 20, 30]);
+    assert_eq!(alpha, [255, 40]);
+}
+
+#[test]
+fn scissors_must_fit_their_target() {
+    use egui_euc::Scissor;
+
+    let buffer = Buffer2d::fill([4, 3], BLACK);
+    assert!(Scissor::try_new(&buffer, 1, 1, 3, 2).is_ok());
+    assert!(Scissor::try_new(&buffer, 4, 3, 0, 0).is_ok());
+
+    let error = Scissor::try_new(&buffer, 2, 0, 3, 1).err().unwrap();
+    assert_eq!((error.rect, error.size), ([2, 0, 3, 1], [4, 3]));
+    assert_eq!(
+        error.to_string(),
+        "scissor rectangle 3x1 at (2, 0) exceeds the 4x3 target"
+    );
+    assert!(Scissor::try_new(&buffer, 0, 1, 1, 3).is_err());
+    // Overflowing sums must not wrap around into range
+    assert!(Scissor::try_new(&buffer, usize::MAX, 0, 2, 1).is_err());
+}
+
+#[test]
+fn content_bounds_fit_the_visible_pixels() {
+    let mut buffer = Buffer2d::fill([8, 6], 0);
+    assert_eq!(egui_euc::content_bounds(&buffer), None);
+
+    // Visible alpha counts, whatever the color
+    buffer.write(2, 4, u32::from_le_bytes([0, 0, 0, 1]));
+    buffer.write(5, 1, WHITE);
+    // Color without alpha doesn't
+    buffer.write(7, 5, u32::from_le_bytes([255, 255, 255, 0]));
+    assert_eq!(egui_euc::content_bounds(&buffer), Some([2, 1, 4, 4]));
+}
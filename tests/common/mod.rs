@@ -0,0 +1,109 @@
+//! Scenes shared by the integration tests.
+
+#![allow(dead_code)]
+
+use egui::{epaint, pos2, vec2, ClippedPrimitive, Color32, ColorImage, Rect, TextureId};
+use egui::{TextureOptions, TexturesDelta};
+
+pub const SCREEN_SIZE: [usize; 2] = [64, 48];
+
+/// Uploads a white image to the font atlas id, which every colored mesh samples at its UV.
+pub fn white_texture() -> TexturesDelta {
+    solid_texture(TextureId::default(), [4, 4], Color32::WHITE)
+}
+
+/// Uploads an image of `size` filled with `color` to texture `id`.
+pub fn solid_texture(id: TextureId, size: [usize; 2], color: Color32) -> TexturesDelta {
+    let image = ColorImage::new(size, vec![color; size[0] * size[1]]);
+    let delta = epaint::ImageDelta::full(image, TextureOptions::NEAREST);
+    TexturesDelta {
+        set: vec![(id, delta)],
+        free: Vec::new(),
+    }
+}
+
+/// A rectangle of `color`, clipped to the whole screen.
+pub fn rect(min: [f32; 2], size: [f32; 2], color: Color32) -> ClippedPrimitive {
+    let rect = Rect::from_min_size(pos2(min[0], min[1]), vec2(size[0], size[1]));
+    let mut mesh = epaint::Mesh::default();
+    mesh.add_colored_rect(rect, color);
+    ClippedPrimitive {
+        clip_rect: Rect::EVERYTHING,
+        primitive: epaint::Primitive::Mesh(mesh),
+    }
+}
+
+/// A rectangle sampling all of texture `id`.
+pub fn textured_rect(id: TextureId, min: [f32; 2], size: [f32; 2]) -> ClippedPrimitive {
+    let rect = Rect::from_min_size(pos2(min[0], min[1]), vec2(size[0], size[1]));
+    let mut mesh = epaint::Mesh::with_texture(id);
+    mesh.add_rect_with_uv(
+        rect,
+        Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
+        Color32::WHITE,
+    );
+    ClippedPrimitive {
+        clip_rect: Rect::EVERYTHING,
+        primitive: epaint::Primitive::Mesh(mesh),
+    }
+}
+
+/// A triangle of `color` through `points`.
+pub fn triangle(points: [[f32; 2]; 3], color: Color32) -> ClippedPrimitive {
+    let mut mesh = epaint::Mesh::default();
+    for [x, y] in points {
+        mesh.colored_vertex(pos2(x, y), color);
+    }
+    mesh.add_triangle(0, 1, 2);
+    ClippedPrimitive {
+        clip_rect: Rect::EVERYTHING,
+        primitive: epaint::Primitive::Mesh(mesh),
+    }
+}
+
+/// Overlapping opaque and translucent shapes, with anti-aliased edges.
+pub fn scene() -> Vec<ClippedPrimitive> {
+    vec![
+        rect([0.0, 0.0], [64.0, 48.0], Color32::from_rgb(30, 30, 40)),
+        rect([4.5, 4.5], [30.0, 20.0], Color32::from_rgb(200, 80, 20)),
+        rect(
+            [20.0, 10.0],
+            [30.0, 30.0],
+            Color32::from_rgba_unmultiplied(20, 120, 220, 128),
+        ),
+        triangle(
+            [[10.0, 40.0], [40.0, 2.0], [60.3, 44.7]],
+            Color32::from_rgba_unmultiplied(250, 250, 250, 90),
+        ),
+    ]
+}
+
+/// The pixels of one frame of `primitives` painted from scratch by a painter set up by `setup`.
+pub fn full_repaint(
+    setup: impl FnOnce(&mut egui_euc::Painter),
+    textures_delta: &TexturesDelta,
+    primitives: &[ClippedPrimitive],
+) -> Vec<u32> {
+    let mut painter = egui_euc::Painter::new();
+    setup(&mut painter);
+    painter
+        .paint_and_update_textures(textures_delta, primitives, 1.0, SCREEN_SIZE)
+        .raw()
+        .to_vec()
+}
+
+/// Uploads an image of `size` to texture `id` with a different color at every texel.
+pub fn gradient_texture(id: TextureId, size: [usize; 2]) -> TexturesDelta {
+    let [width, height] = size;
+    let pixels = (0..width * height)
+        .map(|i| {
+            let (x, y) = (i % width, i / width);
+            Color32::from_rgb((x * 255 / width) as u8, (y * 255 / height) as u8, 128)
+        })
+        .collect();
+    let delta = epaint::ImageDelta::full(ColorImage::new(size, pixels), TextureOptions::NEAREST);
+    TexturesDelta {
+        set: vec![(id, delta)],
+        free: Vec::new(),
+    }
+}
@@ -0,0 +1,121 @@
+//! Debug output of `Painter`.
+
+mod common;
+
+use common::{rect, textured_rect, white_texture, SCREEN_SIZE};
+use egui::{pos2, Color32, Rect};
+use egui_euc::{DebugMode, Painter};
+use euc::{Buffer2d, Target};
+
+const MAGENTA: u32 = u32::from_le_bytes([255, 0, 255, 255]);
+const WHITE: u32 = u32::MAX;
+
+fn pixel(pixels: &[u32], x: usize, y: usize) -> u32 {
+    pixels[y * SCREEN_SIZE[0] + x]
+}
+
+#[test]
+fn clip_rects_are_outlined() {
+    let green = Color32::from_rgb(0, 160, 0);
+    let clipped = |min: [f32; 2], max: [f32; 2]| {
+        let mut primitive = rect([0.0, 0.0], [64.0, 48.0], green);
+        primitive.clip_rect = Rect::from_min_max(pos2(min[0], min[1]), pos2(max[0], max[1]));
+        primitive
+    };
+    let primitives = [
+        clipped([10.0, 10.0], [30.0, 25.0]),
+        clipped([40.0, 5.0], [60.0, 40.0]),
+    ];
+
+    let mut painter = Painter::new();
+    painter.set_debug_mode(DebugMode::ClipRects);
+    let frame = painter.paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    let pixels = frame.raw();
+
+    // Edges and corners of both rects, which are [10, 10] to [29, 24] and [40, 5] to [59, 39]
+    for [x, y] in [
+        [10, 10],
+        [20, 10],
+        [29, 10],
+        [10, 17],
+        [29, 17],
+        [10, 24],
+        [29, 24],
+        [40, 5],
+        [59, 5],
+        [40, 39],
+        [59, 39],
+        [50, 39],
+    ] {
+        assert_eq!(pixel(pixels, x, y), MAGENTA, "[{x}, {y}]");
+    }
+    // Inside the outlines the meshes show, and outside nothing is drawn
+    let green = u32::from_le_bytes(green.to_array());
+    assert_eq!(pixel(pixels, 20, 17), green);
+    assert_eq!(pixel(pixels, 50, 20), green);
+    for [x, y] in [[9, 10], [20, 9], [30, 17], [20, 25], [35, 20], [60, 20]] {
+        assert_eq!(pixel(pixels, x, y), 0, "[{x}, {y}]");
+    }
+}
+
+#[test]
+fn wireframes_draw_triangle_edges() {
+    let dark = Color32::from_rgb(20, 20, 40);
+    let primitives = [rect([10.0, 10.0], [20.0, 20.0], dark)];
+    let paint = |debug_mode| {
+        let mut painter = Painter::new();
+        painter.set_debug_mode(debug_mode);
+        painter
+            .paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE)
+            .raw()
+            .to_vec()
+    };
+    let (plain, wireframe) = (paint(DebugMode::None), paint(DebugMode::Wireframe));
+
+    // The rect's two triangles meet along the diagonal from [30, 10] to [10, 30], which passes
+    // through the center of pixel [19, 20]
+    let [dark, cyan] =
+        [dark, Color32::from_rgb(0, 255, 255)].map(|c| u32::from_le_bytes(c.to_array()));
+    assert_eq!(pixel(&plain, 19, 20), dark);
+    assert_eq!(pixel(&wireframe, 19, 20), cyan);
+    // Away from the edges the mesh shows as usual
+    assert_eq!(pixel(&wireframe, 14, 14), dark);
+    assert_eq!(pixel(&wireframe, 25, 25), dark);
+}
+
+#[test]
+fn recorded_uvs_map_pixels_back_to_the_texture() {
+    let primitives = [textured_rect(
+        egui::TextureId::default(),
+        [0.0, 0.0],
+        [32.0, 32.0],
+    )];
+    let mut painter = Painter::new();
+    painter.paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    assert_eq!(painter.sampled_uv_at(8, 24), None);
+
+    painter.set_record_uvs(true);
+    painter.paint_and_update_textures(&Default::default(), &primitives, 1.0, SCREEN_SIZE);
+    // Sampled at the pixel's center
+    let uv = painter.sampled_uv_at(8, 24).unwrap();
+    let expected = pos2(8.5 / 32.0, 24.5 / 32.0);
+    assert!((uv - expected).length() < 1e-3, "{uv:?}");
+    // Pixels no mesh covered, and pixels outside of the frame, have none
+    assert_eq!(painter.sampled_uv_at(40, 8), None);
+    assert_eq!(painter.sampled_uv_at(SCREEN_SIZE[0], 0), None);
+}
+
+#[test]
+fn checkerboards_show_through_transparency() {
+    let mut buffer = Buffer2d::fill([4, 2], 0);
+    // Opaque pixels hide the backdrop
+    buffer.write(3, 1, u32::from_le_bytes([255, 0, 0, 255]));
+    let preview = egui_euc::composite_over_checkerboard(&buffer, 2);
+
+    let [grey, red] = [[204, 204, 204, 255], [255, 0, 0, 255]].map(u32::from_le_bytes);
+    // Cells of 2x2 pixels, starting light
+    assert_eq!(
+        preview.raw(),
+        [WHITE, WHITE, grey, grey, WHITE, WHITE, grey, red]
+    );
+}
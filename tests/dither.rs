@@ -0,0 +1,57 @@
+//! Dithering output with `Painter::set_dither`.
+
+mod common;
+
+use common::{full_repaint, rect, white_texture, SCREEN_SIZE};
+use egui::{ClippedPrimitive, Color32};
+
+/// Translucent white over black, which lands between two sRGB steps.
+fn scene() -> Vec<ClippedPrimitive> {
+    vec![
+        rect([0.0, 0.0], [64.0, 48.0], Color32::BLACK),
+        rect(
+            [0.0, 0.0],
+            [64.0, 48.0],
+            Color32::from_rgba_unmultiplied(255, 255, 255, 101),
+        ),
+    ]
+}
+
+/// Red channels of the 4x4 block of `pixels` at `[8, 8]`, away from every edge.
+fn block(pixels: &[u32]) -> Vec<u8> {
+    let width = SCREEN_SIZE[0];
+    (8..12)
+        .flat_map(|y| (8..12).map(move |x| pixels[y * width + x].to_le_bytes()[0]))
+        .collect()
+}
+
+#[test]
+fn dithering_breaks_up_flat_quantization() {
+    let plain = block(&full_repaint(|_| {}, &white_texture(), &scene()));
+    assert!(plain.iter().all(|&r| r == plain[0]));
+
+    let dithered = block(&full_repaint(
+        |painter| painter.set_dither(true),
+        &white_texture(),
+        &scene(),
+    ));
+    assert!(dithered.iter().any(|&r| r != dithered[0]), "{dithered:?}");
+    assert!(
+        dithered.iter().all(|r| r.abs_diff(plain[0]) <= 1),
+        "{dithered:?}"
+    );
+}
+
+#[test]
+fn dithering_can_be_turned_off() {
+    let plain = full_repaint(|_| {}, &white_texture(), &scene());
+    let toggled = full_repaint(
+        |painter| {
+            painter.set_dither(true);
+            painter.set_dither(false);
+        },
+        &white_texture(),
+        &scene(),
+    );
+    assert!(plain == toggled);
+}
@@ -0,0 +1,35 @@
+//! Recording the meshes painted, with the `draw-records` feature.
+
+#![cfg(feature = "draw-records")]
+
+mod common;
+
+use common::{scene, textured_rect, white_texture, SCREEN_SIZE};
+use egui::{pos2, Rect, TextureFilter, TextureId, TextureWrapMode};
+use egui_euc::{DrawRecord, Painter};
+
+#[test]
+fn records_match_the_scene() {
+    let mut primitives = scene();
+    // Clipped away entirely, so never drawn
+    let mut hidden = textured_rect(TextureId::default(), [0.0, 0.0], [8.0, 8.0]);
+    hidden.clip_rect = Rect::from_min_max(pos2(100.0, 100.0), pos2(120.0, 120.0));
+    primitives.push(hidden);
+
+    let mut painter = Painter::new();
+    let expected: Vec<_> = [2, 2, 2, 1]
+        .into_iter()
+        .map(|triangle_count| DrawRecord {
+            texture_id: TextureId::default(),
+            clip_rect: Rect::EVERYTHING,
+            triangle_count,
+            filter: TextureFilter::Nearest,
+            wrap_mode: TextureWrapMode::ClampToEdge,
+        })
+        .collect();
+    for _ in 0..2 {
+        painter.paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+        // Each frame replaces the records of the last
+        assert_eq!(painter.draw_records(), expected);
+    }
+}
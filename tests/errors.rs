@@ -0,0 +1,134 @@
+//! Input the painter skips and reports instead of panicking.
+
+mod common;
+
+use common::{full_repaint, scene, solid_texture, white_texture, SCREEN_SIZE};
+use egui::{epaint, ColorImage, TextureId, TextureOptions, TexturesDelta};
+use egui_euc::{MeshError, Painter, PainterError};
+
+fn empty_image(id: TextureId) -> TexturesDelta {
+    let image = ColorImage::new([0, 0], Vec::new());
+    TexturesDelta {
+        set: vec![(id, epaint::ImageDelta::full(image, TextureOptions::NEAREST))],
+        free: Vec::new(),
+    }
+}
+
+#[test]
+fn empty_image_for_a_new_texture_is_skipped() {
+    let id = TextureId::User(1);
+    let mut painter = Painter::new();
+    painter.paint_and_update_textures(&empty_image(id), &[], 1.0, SCREEN_SIZE);
+    assert_eq!(painter.last_errors(), [PainterError::EmptyImage { id }]);
+}
+
+#[test]
+fn empty_image_keeps_the_old_texture() {
+    let primitives = scene();
+    let mut painter = Painter::new();
+    painter.paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    let painted = painter.paint_and_update_textures(
+        &empty_image(TextureId::default()),
+        &primitives,
+        1.0,
+        SCREEN_SIZE,
+    );
+    assert_eq!(
+        painter.last_errors(),
+        [PainterError::EmptyImage {
+            id: TextureId::default()
+        }]
+    );
+    assert!(painted.raw() == full_repaint(|_| {}, &white_texture(), &primitives).as_slice());
+}
+
+#[test]
+fn errors_are_cleared_by_the_next_frame() {
+    let id = TextureId::User(1);
+    let mut painter = Painter::new();
+    painter.paint_and_update_textures(&empty_image(id), &[], 1.0, SCREEN_SIZE);
+    let image = solid_texture(id, [2, 2], egui::Color32::RED);
+    painter.paint_and_update_textures(&image, &[], 1.0, SCREEN_SIZE);
+    assert!(painter.last_errors().is_empty());
+}
+
+#[test]
+fn meshes_of_missing_textures_are_skipped() {
+    let id = TextureId::User(9);
+    let mut primitives = scene();
+    primitives.push(common::textured_rect(id, [0.0, 0.0], [64.0, 48.0]));
+    primitives.push(common::textured_rect(id, [8.0, 8.0], [8.0, 8.0]));
+
+    let mut painter = Painter::new();
+    let painted =
+        painter.paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    // Reported once, however many meshes sample it
+    assert_eq!(painter.last_errors(), [PainterError::MissingTexture { id }]);
+    assert!(painted.raw() == full_repaint(|_| {}, &white_texture(), &scene()).as_slice());
+}
+
+#[test]
+fn late_texture_updates_are_reported() {
+    let id = TextureId::User(9);
+    let mut painter = Painter::new();
+    let pixels = euc::Buffer2d::fill([1, 1], egui::Rgba::WHITE);
+    assert_eq!(
+        painter.update_texture_region(id, &pixels, [0, 0, 1, 1], [0, 0]),
+        Err(PainterError::MissingTexture { id })
+    );
+    assert_eq!(
+        painter.paint_into_texture(id, &scene(), 1.0),
+        Err(PainterError::MissingTexture { id })
+    );
+
+    let mut partial = solid_texture(id, [2, 2], egui::Color32::RED);
+    partial.set[0].1.pos = Some([0, 0]);
+    painter.paint_and_update_textures(&partial, &[], 1.0, SCREEN_SIZE);
+    assert_eq!(painter.last_errors(), [PainterError::MissingTexture { id }]);
+}
+
+#[test]
+fn invalid_meshes_are_reported() {
+    let mut primitives = scene();
+    let mut broken = common::rect([4.0, 4.0], [8.0, 8.0], egui::Color32::RED);
+    if let epaint::Primitive::Mesh(mesh) = &mut broken.primitive {
+        mesh.indices.push(0);
+    }
+    primitives.insert(1, broken);
+
+    let mut painter = Painter::new();
+    let painted =
+        painter.paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    let error = MeshError::IncompleteTriangle { index_count: 7 };
+    assert_eq!(painter.last_mesh_errors().collect::<Vec<_>>(), [(1, error)]);
+    assert_eq!(
+        painter.last_errors(),
+        [PainterError::InvalidMesh {
+            primitive: 1,
+            error
+        }]
+    );
+    assert_eq!(painter.paint_stats().invalid_meshes, 1);
+    assert!(painted.raw() == full_repaint(|_| {}, &white_texture(), &scene()).as_slice());
+}
+
+#[test]
+fn deltas_short_of_pixels_update_what_they_have() {
+    let id = TextureId::User(1);
+    let primitives = [common::textured_rect(id, [0.0, 0.0], [16.0, 16.0])];
+    let mut painter = Painter::new();
+    let white = solid_texture(id, [4, 4], egui::Color32::WHITE);
+    painter.paint_and_update_textures(&white, &primitives, 1.0, SCREEN_SIZE);
+
+    // Sized 4x4, but only holding the top two rows
+    let mut image = ColorImage::new([4, 4], vec![egui::Color32::RED; 16]);
+    image.pixels.truncate(8);
+    let short = TexturesDelta {
+        set: vec![(id, epaint::ImageDelta::full(image, TextureOptions::NEAREST))],
+        free: Vec::new(),
+    };
+    let painted = painter.paint_and_update_textures(&short, &primitives, 1.0, SCREEN_SIZE);
+    let pixel = |x, y| painted.raw()[y * SCREEN_SIZE[0] + x];
+    assert_eq!(pixel(8, 2), u32::from_le_bytes([255, 0, 0, 255]));
+    assert_eq!(pixel(8, 12), u32::MAX);
+}
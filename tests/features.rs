@@ -0,0 +1,15 @@
+//! Optional features reported at runtime by `features()`.
+
+use egui_euc::features;
+
+#[test]
+fn features_match_the_compiled_cfg() {
+    // Integration tests are compiled with the same features as the crate
+    let features = features();
+    assert_eq!(features.par, cfg!(feature = "par"));
+    assert_eq!(features.draw_records, cfg!(feature = "draw-records"));
+    assert_eq!(features.web, cfg!(feature = "web"));
+    assert_eq!(features.fixed_point, cfg!(feature = "fixed-point"));
+    assert_eq!(features.test_util, cfg!(feature = "test-util"));
+    assert_eq!(features.winit, cfg!(feature = "winit"));
+}
@@ -0,0 +1,275 @@
+//! Painting frames of egui primitives with `Painter`.
+
+mod common;
+
+use common::{full_repaint, rect, scene, textured_rect, triangle, white_texture, SCREEN_SIZE};
+use egui::epaint::Primitive;
+use egui::{TextureId, TexturesDelta};
+use egui_euc::{Painter, PainterError};
+
+#[test]
+fn paint_iter_draws_only_what_it_yields() {
+    let primitives = scene();
+    let opaque = |primitive: &&egui::ClippedPrimitive| match &primitive.primitive {
+        Primitive::Mesh(mesh) => mesh.vertices.iter().all(|vertex| vertex.color.is_opaque()),
+        Primitive::Callback(_) => false,
+    };
+
+    let mut painter = Painter::new();
+    let filtered = painter.paint_iter(
+        &white_texture(),
+        primitives.iter().filter(opaque),
+        1.0,
+        SCREEN_SIZE,
+    );
+
+    let kept: Vec<_> = primitives.iter().filter(opaque).cloned().collect();
+    assert_eq!(kept.len(), 2);
+    assert_eq!(
+        filtered.raw(),
+        full_repaint(|_| {}, &white_texture(), &kept)
+    );
+    assert_ne!(
+        filtered.raw(),
+        full_repaint(|_| {}, &white_texture(), &primitives)
+    );
+}
+
+#[test]
+fn painting_at_the_tessellation_scale_keeps_geometry() {
+    // 8x8 points at 2 pixels per point, so pixels 16..32
+    let shape = egui::epaint::ClippedShape {
+        clip_rect: egui::Rect::EVERYTHING,
+        shape: egui::Shape::rect_filled(
+            egui::Rect::from_min_size(egui::pos2(8.0, 8.0), egui::vec2(8.0, 8.0)),
+            0.0,
+            egui::Color32::WHITE,
+        ),
+    };
+    let primitives = egui::epaint::Tessellator::new(2.0, Default::default(), [4, 4], Vec::new())
+        .tessellate_shapes(vec![shape]);
+
+    let alpha = |pixels_per_point: f32| {
+        let buffer = Painter::new().paint_and_update_textures(
+            &white_texture(),
+            &primitives,
+            pixels_per_point,
+            SCREEN_SIZE,
+        );
+        [[12, 12], [24, 24]].map(|[x, y]| Painter::pixel_alpha_at(&buffer, x, y))
+    };
+    assert_eq!(alpha(2.0), [0, 255]);
+    // Mistakenly painted at 1.0, the rect lands at half the size and position
+    assert_eq!(alpha(1.0), [255, 0]);
+}
+
+#[test]
+fn frames_span_several_paints() {
+    let primitives = scene();
+    let (below, above) = primitives.split_at(2);
+    let mut painter = Painter::new();
+    painter.begin_frame(&white_texture());
+    let mut buffer = euc::Buffer2d::fill(SCREEN_SIZE, 0);
+    painter.paint(&mut buffer, below, 1.0);
+    painter.paint(&mut buffer, above, 1.0);
+    painter.end_frame();
+
+    assert_eq!(
+        buffer.raw(),
+        full_repaint(|_| {}, &white_texture(), &primitives)
+    );
+    assert_eq!(painter.paint_stats().meshes, primitives.len());
+}
+
+#[test]
+fn textures_freed_by_a_frame_last_until_it_ends() {
+    let font = [textured_rect(TextureId::default(), [0.0, 0.0], [8.0, 8.0])];
+    let mut painter = Painter::new();
+    painter.paint_and_update_textures(&white_texture(), &font, 1.0, SCREEN_SIZE);
+
+    let free = TexturesDelta {
+        set: Vec::new(),
+        free: vec![TextureId::default()],
+    };
+    let mut buffer = euc::Buffer2d::fill(SCREEN_SIZE, 0);
+    painter.begin_frame(&free);
+    painter.paint(&mut buffer, &font, 1.0);
+    painter.end_frame();
+    assert!(painter.last_errors().is_empty());
+    assert!(Painter::is_opaque_at(&buffer, 4, 4));
+
+    painter.paint_and_update_textures(&TexturesDelta::default(), &font, 1.0, SCREEN_SIZE);
+    assert_eq!(
+        painter.last_errors(),
+        [PainterError::MissingTexture {
+            id: TextureId::default()
+        }]
+    );
+}
+
+#[test]
+fn pixel_writes_count_overdraw() {
+    // Overlapping by half, so a quarter of the pixels are written twice
+    let primitives = [
+        rect([0.0, 0.0], [10.0, 10.0], egui::Color32::RED),
+        rect([5.0, 0.0], [10.0, 10.0], egui::Color32::BLUE),
+    ];
+    let mut painter = Painter::new();
+    painter.paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    assert_eq!(painter.paint_stats().pixels_written, 0);
+
+    painter.set_count_pixels(true);
+    painter.paint_and_update_textures(&TexturesDelta::default(), &primitives, 1.0, SCREEN_SIZE);
+    let stats = painter.paint_stats();
+    assert_eq!(stats.pixels_written, 200);
+    assert_eq!(stats.bytes_written(), 800);
+}
+
+#[test]
+fn backgrounds_show_through_where_nothing_is_drawn() {
+    let [width, height] = [32, 16];
+    let mut background = euc::Buffer2d::fill([width, height], 0);
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = u32::from_le_bytes([x as u8 * 8, y as u8 * 16, 100, 255]);
+            euc::Target::write(&mut background, x, y, pixel);
+        }
+    }
+    let veil = egui::Color32::from_black_alpha(128);
+    let primitives = [rect([8.0, 4.0], [8.0, 8.0], veil)];
+
+    let output =
+        Painter::new().paint_over_background(&background, &white_texture(), &primitives, 1.0);
+    assert_eq!(euc::Texture::size(&output), [width, height]);
+    for y in 0..height {
+        for x in 0..width {
+            let (below, pixel) = (background.raw()[y * width + x], output.raw()[y * width + x]);
+            if (8..16).contains(&x) && (4..12).contains(&y) {
+                let blended = egui_euc::BlendOp::Over
+                    .blend(egui_euc::unpack_pixel(below), egui::Rgba::from(veil));
+                assert_eq!(pixel, egui_euc::pack_pixel(blended), "at {x}, {y}");
+            } else {
+                assert_eq!(pixel, below, "at {x}, {y}");
+            }
+        }
+    }
+}
+
+#[test]
+fn triangle_strips_convert_to_lists() {
+    // Windings alternate along a strip, so every other triangle is flipped back
+    assert_eq!(
+        egui_euc::triangle_strip_to_list(&[0, 1, 2, 3]),
+        [0, 1, 2, 2, 1, 3]
+    );
+    // Two strips joined by repeating vertices, whose degenerate triangles are dropped. Parity
+    // carries on through them, so the second strip starts flipped
+    assert_eq!(
+        egui_euc::triangle_strip_to_list(&[0, 1, 2, 2, 3, 3, 4, 5]),
+        [0, 1, 2, 4, 3, 5]
+    );
+    assert!(egui_euc::triangle_strip_to_list(&[0, 1]).is_empty());
+
+    // A strip of a rect paints like egui's own rect
+    let color = egui::Color32::RED;
+    let mut mesh = egui::epaint::Mesh::default();
+    for [x, y] in [[4.0, 4.0], [4.0, 12.0], [20.0, 4.0], [20.0, 12.0]] {
+        mesh.colored_vertex(egui::pos2(x, y), color);
+    }
+    mesh.indices = egui_euc::triangle_strip_to_list(&[0, 1, 2, 3]);
+    let strip = [egui::ClippedPrimitive {
+        clip_rect: egui::Rect::EVERYTHING,
+        primitive: Primitive::Mesh(mesh),
+    }];
+    assert_eq!(
+        full_repaint(|_| {}, &white_texture(), &strip),
+        full_repaint(
+            |_| {},
+            &white_texture(),
+            &[rect([4.0, 4.0], [16.0, 8.0], color)]
+        )
+    );
+}
+
+#[test]
+fn cached_layers_composite_like_a_full_repaint() {
+    let primitives = scene();
+    let (static_part, dynamic) = primitives.split_at(2);
+    let mut painter = Painter::new();
+    painter.paint_and_update_textures(&white_texture(), &[], 1.0, SCREEN_SIZE);
+    let layer = painter.rasterize_to_layer(static_part, 1.0, SCREEN_SIZE);
+
+    for _ in 0..2 {
+        let frame = painter.paint_over_background(&layer, &TexturesDelta::default(), dynamic, 1.0);
+        assert_eq!(
+            frame.raw(),
+            full_repaint(|_| {}, &white_texture(), &primitives)
+        );
+    }
+}
+
+#[test]
+fn degenerate_triangles_are_skipped() {
+    let mut primitives = scene();
+    let collinear = [[4.0, 4.0], [20.0, 12.0], [36.0, 20.0]];
+    primitives.push(triangle(collinear, egui::Color32::RED));
+    let mut painter = Painter::new();
+    let painted =
+        painter.paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    assert_eq!(painter.paint_stats().degenerate_triangles, 1);
+    assert_eq!(
+        painted.raw(),
+        full_repaint(|_| {}, &white_texture(), &scene())
+    );
+
+    // Raised above the 50 square pixels of each half of a 10x10 rect
+    let square = [rect([0.0, 0.0], [10.0, 10.0], egui::Color32::RED)];
+    painter.set_degenerate_triangle_area(60.0);
+    let painted =
+        painter.paint_and_update_textures(&TexturesDelta::default(), &square, 1.0, SCREEN_SIZE);
+    assert_eq!(painter.paint_stats().degenerate_triangles, 2);
+    assert!(painted.raw().iter().all(|&pixel| pixel == 0));
+}
+
+#[test]
+fn textures_persist_across_frames() {
+    let id = TextureId::User(1);
+    let red = egui::Color32::RED;
+    let primitives = [textured_rect(id, [8.0, 8.0], [16.0, 16.0])];
+    let mut painter = Painter::new();
+    let mut textures_delta = common::solid_texture(id, [2, 2], red);
+    for frame in 0..3 {
+        let image =
+            painter.paint_and_update_textures(&textures_delta, &primitives, 1.0, SCREEN_SIZE);
+        assert_eq!(
+            image.raw()[16 * SCREEN_SIZE[0] + 16],
+            u32::from_le_bytes(red.to_array()),
+            "texture missing in frame {frame}"
+        );
+        // Only the first frame uploads it
+        textures_delta = TexturesDelta::default();
+    }
+}
+
+#[test]
+fn paint_with_presents_a_reused_buffer() {
+    let white = u32::MAX;
+    let mut painter = Painter::new();
+    let primitives = [rect([4.0, 4.0], [8.0, 8.0], egui::Color32::WHITE)];
+    let (pixel, first) =
+        painter.paint_with(&white_texture(), &primitives, 1.0, SCREEN_SIZE, |image| {
+            (image.raw()[6 * SCREEN_SIZE[0] + 6], image.raw().as_ptr())
+        });
+    assert_eq!(pixel, white);
+
+    // The next frame reuses the buffer, cleared of the last one
+    let (drawn, second) =
+        painter.paint_with(&TexturesDelta::default(), &[], 1.0, SCREEN_SIZE, |image| {
+            (
+                image.raw().iter().any(|&pixel| pixel != 0),
+                image.raw().as_ptr(),
+            )
+        });
+    assert!(!drawn);
+    assert_eq!(first, second);
+}
@@ -0,0 +1,84 @@
+//! Drawing without a frame of egui primitives.
+
+use egui::{epaint, pos2, Color32, Rgba};
+use egui_euc::UvSampler;
+use euc::{Buffer2d, Texture};
+
+/// Samples white everywhere, leaving vertex colors as they are.
+struct White;
+
+impl UvSampler for White {
+    fn sample_uv(&self, _uv: [f32; 2]) -> Rgba {
+        Rgba::WHITE
+    }
+}
+
+fn vertex(x: f32, y: f32, color: Color32) -> epaint::Vertex {
+    epaint::Vertex {
+        pos: pos2(x, y),
+        uv: pos2(0.0, 0.0),
+        color,
+    }
+}
+
+#[test]
+fn triangle_colors_are_interpolated() {
+    let mut target = Buffer2d::fill([64, 48], 0u32);
+    let vertices = [
+        vertex(0.0, 0.0, Color32::RED),
+        vertex(64.0, 0.0, Color32::GREEN),
+        vertex(0.0, 48.0, Color32::BLUE),
+    ];
+    egui_euc::rasterize_triangle(&mut target, vertices, White);
+    let rgba = |x, y| target.read([x, y]).to_le_bytes();
+
+    // Near each corner its color dominates
+    for ([x, y], channel) in [([1, 1], 0), ([60, 1], 1), ([1, 45], 2)] {
+        let pixel = rgba(x, y);
+        assert_eq!(pixel[3], 255);
+        for (i, &value) in pixel[..3].iter().enumerate() {
+            if i == channel {
+                assert!(value > 230, "{pixel:?} at [{x}, {y}]");
+            } else {
+                assert!(value < 110, "{pixel:?} at [{x}, {y}]");
+            }
+        }
+    }
+    // Around the centroid the colors mix evenly
+    let [r, g, b, _] = rgba(21, 15);
+    assert!(r.abs_diff(g) <= 12 && g.abs_diff(b) <= 12 && r.abs_diff(b) <= 12);
+    // Outside of the triangle nothing is drawn
+    assert_eq!(rgba(60, 45), [0; 4]);
+}
+
+#[test]
+fn shapes_are_painted_without_a_frame() {
+    // Any context can provide fonts, once a pass has set them up
+    let ctx = egui::Context::default();
+    let _ = ctx.run(Default::default(), |_| {});
+
+    let circle = egui::Shape::circle_filled(pos2(32.0, 24.0), 10.0, Color32::RED);
+    let mut painter = egui_euc::Painter::new();
+    let frame = ctx.fonts(|fonts| {
+        painter.paint_shapes(
+            vec![circle],
+            fonts,
+            epaint::TessellationOptions::default(),
+            1.0,
+            [64, 48],
+        )
+    });
+    let rgba = |x, y| frame.read([x, y]).to_le_bytes();
+
+    assert_eq!(rgba(32, 24), [255, 0, 0, 255]);
+    assert_eq!(rgba(32, 17), [255, 0, 0, 255]);
+    assert_eq!(rgba(32, 36), [0; 4]);
+    assert_eq!(rgba(0, 0), [0; 4]);
+    // About the area of the circle is covered
+    let covered = frame
+        .raw()
+        .iter()
+        .filter(|p| p.to_le_bytes()[3] >= 128)
+        .count();
+    assert!((290..340).contains(&covered), "{covered} pixels covered");
+}
@@ -0,0 +1,57 @@
+//! Occlusion culling must never change what is painted.
+
+mod common;
+
+use common::{rect, scene, white_texture, SCREEN_SIZE};
+use egui::{epaint, pos2, ClippedPrimitive, Color32, Rect};
+use egui_euc::Painter;
+
+fn paint(primitives: &[ClippedPrimitive], occlusion_culling: bool) -> Vec<u32> {
+    let mut painter = Painter::new();
+    painter.set_occlusion_culling(occlusion_culling);
+    painter
+        .paint_and_update_textures(&white_texture(), primitives, 1.0, SCREEN_SIZE)
+        .raw()
+        .to_vec()
+}
+
+#[test]
+fn culling_keeps_pixels() {
+    let mut primitives = scene();
+    // Opaque panels over everything drawn before
+    primitives.push(rect([2.0, 2.0], [30.0, 30.0], Color32::DARK_GRAY));
+    primitives.push(rect(
+        [16.0, 8.0],
+        [40.0, 36.0],
+        Color32::from_rgb(10, 90, 10),
+    ));
+    primitives.push(rect([20.0, 20.0], [8.0, 8.0], Color32::YELLOW));
+
+    let mut painter = Painter::new();
+    painter.set_occlusion_culling(true);
+    painter.paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    assert!(
+        painter.paint_stats().occluded_meshes > 0,
+        "nothing was culled"
+    );
+
+    assert!(paint(&primitives, true) == paint(&primitives, false));
+}
+
+#[test]
+fn half_filled_rectangles_occlude_nothing() {
+    // Opaque vertices at all four corners, but only one triangle between them
+    let mut mesh = epaint::Mesh::default();
+    let corners = [[8.0, 8.0], [40.0, 8.0], [40.0, 40.0], [8.0, 40.0]];
+    for [x, y] in corners {
+        mesh.colored_vertex(pos2(x, y), Color32::BLUE);
+    }
+    mesh.add_triangle(0, 1, 2);
+    let half = ClippedPrimitive {
+        clip_rect: Rect::EVERYTHING,
+        primitive: epaint::Primitive::Mesh(mesh),
+    };
+
+    let primitives = [rect([0.0, 0.0], [64.0, 48.0], Color32::RED), half];
+    assert!(paint(&primitives, true) == paint(&primitives, false));
+}
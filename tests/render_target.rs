@@ -0,0 +1,122 @@
+//! Painting into a custom `RenderTarget` with `Painter::paint_to`.
+
+mod common;
+
+use common::{rect, scene, white_texture, SCREEN_SIZE};
+use egui::Color32;
+use egui_euc::{ColorBlindness, Painter, PostProcess, RenderTarget, Rgb888Target};
+use euc::{Buffer2d, Texture};
+
+/// A target recording which pixels were read or written.
+struct Tracked {
+    pixels: Buffer2d<u32>,
+    touched: Vec<[usize; 2]>,
+}
+
+impl Tracked {
+    fn new() -> Self {
+        Self {
+            pixels: Buffer2d::fill(SCREEN_SIZE, 0),
+            touched: Vec::new(),
+        }
+    }
+}
+
+impl RenderTarget for Tracked {
+    fn width(&self) -> usize {
+        SCREEN_SIZE[0]
+    }
+
+    fn height(&self) -> usize {
+        SCREEN_SIZE[1]
+    }
+
+    fn get_pixel(&self, x: usize, y: usize) -> u32 {
+        self.pixels.read([x, y])
+    }
+
+    fn set_pixel(&mut self, x: usize, y: usize, pixel: u32) {
+        self.touched.push([x, y]);
+        self.pixels.set_pixel(x, y, pixel);
+    }
+}
+
+#[test]
+fn paint_to_matches_paint() {
+    let primitives = scene();
+    let mut painter = Painter::new();
+    painter.begin_frame(&white_texture());
+    let mut target = Tracked::new();
+    painter.paint_to(&mut target, &primitives, 1.0);
+    let mut buffer = Buffer2d::fill(SCREEN_SIZE, 0);
+    painter.paint(&mut buffer, &primitives, 1.0);
+    painter.end_frame();
+
+    assert!(target.pixels.raw() == buffer.raw());
+}
+
+#[test]
+fn paint_to_only_copies_what_primitives_can_touch() {
+    let primitives = [rect([4.0, 6.0], [10.0, 8.0], Color32::RED)];
+    let mut painter = Painter::new();
+    painter.begin_frame(&white_texture());
+    let mut target = Tracked::new();
+    painter.paint_to(&mut target, &primitives, 1.0);
+    assert_eq!(target.touched.len(), 10 * 8);
+    assert!(target
+        .touched
+        .iter()
+        .all(|&[x, y]| (4..14).contains(&x) && (6..14).contains(&y)));
+
+    // Post processing applies to the whole target
+    painter.set_post_process(Some(PostProcess::ColorBlind(ColorBlindness::Protanopia)));
+    let mut target = Tracked::new();
+    painter.paint_to(&mut target, &primitives, 1.0);
+    painter.end_frame();
+    assert_eq!(target.touched.len(), SCREEN_SIZE[0] * SCREEN_SIZE[1]);
+}
+
+#[test]
+fn rgb888_output_is_composited_over_the_background() {
+    let background = Color32::from_rgb(40, 80, 120);
+    let translucent = &scene()[2..];
+    let mut painter = Painter::new();
+    painter.begin_frame(&white_texture());
+
+    // Drawing the background first is what the output should look like
+    let mut expected = Buffer2d::fill(SCREEN_SIZE, 0);
+    let backdrop = rect([0.0, 0.0], [64.0, 48.0], background);
+    painter.paint(
+        &mut expected,
+        std::iter::once(&backdrop).chain(translucent),
+        1.0,
+    );
+    let expected: Vec<u8> = expected
+        .raw()
+        .iter()
+        .flat_map(|pixel| <[u8; 3]>::try_from(&pixel.to_le_bytes()[..3]).unwrap())
+        .collect();
+
+    let mut buffer = Buffer2d::fill(SCREEN_SIZE, 0);
+    painter.paint(&mut buffer, translucent, 1.0);
+    let converted = egui_euc::buffer_to_rgb888(&buffer, background);
+
+    let mut bytes =
+        [background.r(), background.g(), background.b()].repeat(SCREEN_SIZE[0] * SCREEN_SIZE[1]);
+    painter.paint_to(
+        &mut Rgb888Target::new(&mut bytes, SCREEN_SIZE, background),
+        translucent,
+        1.0,
+    );
+    painter.end_frame();
+
+    for (name, rgb) in [("buffer_to_rgb888", &converted), ("Rgb888Target", &bytes)] {
+        assert_eq!(rgb.len(), SCREEN_SIZE[0] * SCREEN_SIZE[1] * 3);
+        let error = rgb
+            .iter()
+            .zip(&expected)
+            .map(|(&a, &b)| a.abs_diff(b))
+            .max();
+        assert!(error <= Some(2), "{name} is off by {error:?}");
+    }
+}
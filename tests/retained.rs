@@ -0,0 +1,158 @@
+//! Frames retained by `Painter::paint_diff` must match a full repaint after anything changes.
+
+mod common;
+
+use common::{full_repaint, rect, scene, solid_texture, textured_rect, white_texture, SCREEN_SIZE};
+use egui::{Rgba, TexturesDelta};
+use egui_euc::{BlendOp, DebugMode, Painter, TextureStorage};
+use euc::Buffer2d;
+
+/// Paints the scene with `paint_diff`, applies `change`, paints it again and compares the second
+/// frame against a painter which had `change` applied from the start.
+fn assert_diff_follows(name: &str, change: impl Fn(&mut Painter)) {
+    let primitives = scene();
+    let mut painter = Painter::new();
+    painter.paint_diff(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    change(&mut painter);
+    let diffed = painter
+        .paint_diff(&TexturesDelta::default(), &primitives, 1.0, SCREEN_SIZE)
+        .raw()
+        .to_vec();
+
+    let expected = full_repaint(&change, &white_texture(), &primitives);
+    assert!(
+        diffed == expected,
+        "paint_diff kept stale pixels after {name}"
+    );
+}
+
+#[test]
+fn unchanged_settings_keep_pixels() {
+    assert_diff_follows("nothing", |_| {});
+}
+
+#[test]
+fn settings_invalidate_retained_frame() {
+    assert_diff_follows("set_global_opacity", |p| p.set_global_opacity(0.5));
+    assert_diff_follows("set_fragment_hook", |p| {
+        p.set_fragment_hook(|color| {
+            Rgba::from_rgba_premultiplied(color.b(), color.g(), color.r(), color.a())
+        })
+    });
+    assert_diff_follows("set_text_gamma", |p| p.set_text_gamma(2.2));
+    assert_diff_follows("set_blend_op", |p| p.set_blend_op(BlendOp::Replace));
+    assert_diff_follows("set_texture_id_remap", |p| {
+        let red = Rgba::from_rgb(1.0, 0.0, 0.0);
+        p.register_user_texture(
+            egui::TextureId::User(7),
+            Buffer2d::fill([1, 1], red),
+            egui::TextureOptions::NEAREST,
+        );
+        p.set_texture_id_remap(|_| egui::TextureId::User(7))
+    });
+    assert_diff_follows("set_debug_mode", |p| p.set_debug_mode(DebugMode::Wireframe));
+    assert_diff_follows("set_clip_feather", |p| p.set_clip_feather(4.0));
+    assert_diff_follows("set_lod_bias", |p| p.set_lod_bias(-1.0));
+    assert_diff_follows("set_srgb_filtering", |p| p.set_srgb_filtering(true));
+    assert_diff_follows("set_occlusion_culling", |p| p.set_occlusion_culling(true));
+    assert_diff_follows("set_degenerate_triangle_area", |p| {
+        p.set_degenerate_triangle_area(1000.0)
+    });
+    assert_diff_follows("set_texture_storage", |p| {
+        p.set_texture_storage(TextureStorage::Alpha8)
+    });
+    assert_diff_follows("set_logical_offset", |p| {
+        p.set_logical_offset(egui::vec2(3.0, 2.0))
+    });
+    assert_diff_follows("set_mirror", |p| p.set_mirror(true, false));
+    assert_diff_follows("set_pixel_aspect_ratio", |p| p.set_pixel_aspect_ratio(2.0));
+}
+
+fn solid(color: Rgba) -> Buffer2d<Rgba> {
+    Buffer2d::fill([4, 4], color)
+}
+
+/// Paints a rectangle of a red user texture with `paint_diff`, applies `change`, and compares the
+/// next frame against painting the rectangle with a green texture from scratch.
+fn assert_texture_change_redraws(name: &str, change: impl FnOnce(&mut Painter)) {
+    let id = egui::TextureId::User(1);
+    let primitives = vec![textured_rect(id, [8.0, 8.0], [32.0, 24.0])];
+    let mut painter = Painter::new();
+    painter.register_user_texture(id, solid(Rgba::RED), egui::TextureOptions::NEAREST);
+    painter.paint_diff(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    change(&mut painter);
+    let diffed = painter
+        .paint_diff(&TexturesDelta::default(), &primitives, 1.0, SCREEN_SIZE)
+        .raw()
+        .to_vec();
+
+    let expected = full_repaint(
+        |p| p.register_user_texture(id, solid(Rgba::GREEN), egui::TextureOptions::NEAREST),
+        &TexturesDelta::default(),
+        &primitives,
+    );
+    assert!(
+        diffed == expected,
+        "paint_diff kept stale pixels after {name}"
+    );
+}
+
+#[test]
+fn texture_changes_outside_of_deltas_redraw() {
+    let id = egui::TextureId::User(1);
+    assert_texture_change_redraws("register_user_texture", |p| {
+        p.register_user_texture(id, solid(Rgba::GREEN), egui::TextureOptions::NEAREST)
+    });
+    assert_texture_change_redraws("update_texture_region", |p| {
+        p.update_texture_region(id, &solid(Rgba::GREEN), [0, 0, 4, 4], [0, 0])
+            .unwrap()
+    });
+    assert_texture_change_redraws("paint_into_texture", |p| {
+        let green = rect([0.0, 0.0], [4.0, 4.0], egui::Color32::GREEN);
+        p.paint_into_texture(id, [&green], 1.0).unwrap()
+    });
+}
+
+#[test]
+fn texture_uploads_redraw() {
+    let primitives = scene();
+    let mut painter = Painter::new();
+    painter.paint_diff(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    let grey = solid_texture(egui::TextureId::default(), [4, 4], egui::Color32::GRAY);
+    let diffed = painter
+        .paint_diff(&grey, &primitives, 1.0, SCREEN_SIZE)
+        .raw()
+        .to_vec();
+
+    assert!(diffed == full_repaint(|_| {}, &grey, &primitives));
+}
+
+#[test]
+fn buffer_rings_carry_unchanged_regions_forward() {
+    let first = scene();
+    let mut second = scene();
+    second[1] = rect([6.0, 30.0], [10.0, 10.0], egui::Color32::GOLD);
+    let mut third = second.clone();
+    third.push(rect([50.0, 2.0], [8.0, 8.0], egui::Color32::LIGHT_BLUE));
+
+    let mut painter = Painter::new();
+    painter.set_buffer_count(2);
+    let textures_delta = white_texture();
+    let front = painter.paint_buffered(&textures_delta, &first, 1.0, SCREEN_SIZE);
+    let empty = TexturesDelta::default();
+    let back = painter.paint_buffered(&empty, &second, 1.0, SCREEN_SIZE);
+    assert_ne!(front, back);
+
+    let repaint = |primitives| full_repaint(|_| {}, &textures_delta, primitives);
+    // Only the changed rectangle was drawn into the back buffer, the rest came from the front one
+    assert!(painter.buffer(back).raw() == repaint(&second));
+    // The front buffer, which may still be on screen, was left alone
+    assert!(painter.buffer(front).raw() == repaint(&first));
+
+    assert_eq!(
+        painter.paint_buffered(&empty, &third, 1.0, SCREEN_SIZE),
+        front
+    );
+    assert!(painter.buffer(front).raw() == repaint(&third));
+    assert!(painter.buffer(back).raw() == repaint(&second));
+}
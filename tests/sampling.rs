@@ -0,0 +1,623 @@
+//! How meshes sample their textures.
+
+mod common;
+
+use common::{full_repaint, SCREEN_SIZE};
+use egui::{epaint, pos2, ClippedPrimitive, Color32, ColorImage, Rect, TextureId};
+use egui::{TextureOptions, TextureWrapMode, TexturesDelta};
+
+const BLACK: u32 = u32::from_le_bytes([0, 0, 0, 255]);
+const WHITE: u32 = u32::from_le_bytes([255, 255, 255, 255]);
+
+/// Nearest when magnified, linear when minified.
+const MIN_LINEAR: TextureOptions = TextureOptions {
+    magnification: egui::TextureFilter::Nearest,
+    minification: egui::TextureFilter::Linear,
+    ..TextureOptions::NEAREST
+};
+
+/// Uploads `image` to texture `id` with `options`.
+fn texture(id: TextureId, image: ColorImage, options: TextureOptions) -> TexturesDelta {
+    TexturesDelta {
+        set: vec![(id, epaint::ImageDelta::full(image, options))],
+        free: Vec::new(),
+    }
+}
+
+/// A rectangle from `min` to `max` mapping `uv_min` to `uv_max` of texture `id`, clipped to
+/// `clip_rect`.
+fn quad(
+    id: TextureId,
+    [min, max]: [[f32; 2]; 2],
+    [uv_min, uv_max]: [[f32; 2]; 2],
+    clip_rect: Rect,
+) -> ClippedPrimitive {
+    let mut mesh = epaint::Mesh::with_texture(id);
+    mesh.add_rect_with_uv(
+        Rect::from_min_max(pos2(min[0], min[1]), pos2(max[0], max[1])),
+        Rect::from_min_max(pos2(uv_min[0], uv_min[1]), pos2(uv_max[0], uv_max[1])),
+        Color32::WHITE,
+    );
+    ClippedPrimitive {
+        clip_rect,
+        primitive: epaint::Primitive::Mesh(mesh),
+    }
+}
+
+/// An 8x8 checker of single texels, which averages to grey in every 2x2 box and only stays black
+/// and white when sampled nearest.
+fn single_texel_checker() -> ColorImage {
+    let pixels = (0..64)
+        .map(|i| match (i % 8 + i / 8) % 2 {
+            0 => Color32::WHITE,
+            _ => Color32::BLACK,
+        })
+        .collect();
+    ColorImage::new([8, 8], pixels)
+}
+
+/// The `size` by `size` pixels at the top left of a frame of the whole of texture `id` drawn
+/// over them.
+fn draw_square(
+    textures_delta: &TexturesDelta,
+    id: TextureId,
+    size: f32,
+    setup: impl FnOnce(&mut egui_euc::Painter),
+) -> Vec<u32> {
+    let primitives = [quad(
+        id,
+        [[0.0, 0.0], [size, size]],
+        [[0.0, 0.0], [1.0, 1.0]],
+        Rect::EVERYTHING,
+    )];
+    let pixels = full_repaint(setup, textures_delta, &primitives);
+    let size = size as usize;
+    (0..size * size)
+        .map(|i| pixels[i / size * SCREEN_SIZE[0] + i % size])
+        .collect()
+}
+
+/// Like [`draw_square`] at 32 pixels, but uploading `textures_delta` to the existing `painter`.
+fn draw_reupload(
+    painter: &mut egui_euc::Painter,
+    textures_delta: &TexturesDelta,
+    id: TextureId,
+) -> Vec<u32> {
+    let primitives = [quad(
+        id,
+        [[0.0, 0.0], [32.0, 32.0]],
+        [[0.0, 0.0], [1.0, 1.0]],
+        Rect::EVERYTHING,
+    )];
+    let painted = painter.paint_and_update_textures(textures_delta, &primitives, 1.0, SCREEN_SIZE);
+    (0..32 * 32)
+        .map(|i| painted.raw()[i / 32 * SCREEN_SIZE[0] + i % 32])
+        .collect()
+}
+
+/// Whether all `pixels` are black or white, as nearest sampling leaves a black and white texture.
+fn is_sharp(pixels: &[u32]) -> bool {
+    pixels.iter().all(|&pixel| pixel == WHITE || pixel == BLACK)
+}
+
+#[test]
+fn clipped_repeating_quads_tile_seamlessly() {
+    let id = TextureId::User(1);
+    let checker = ColorImage::new(
+        [2, 2],
+        vec![
+            Color32::WHITE,
+            Color32::BLACK,
+            Color32::BLACK,
+            Color32::WHITE,
+        ],
+    );
+    let options = TextureOptions {
+        wrap_mode: TextureWrapMode::Repeat,
+        ..TextureOptions::NEAREST
+    };
+    // Eight by six repeats over the whole screen, so every texel covers 4x4 pixels
+    let primitives = [quad(
+        id,
+        [[0.0, 0.0], [64.0, 48.0]],
+        [[0.0, 0.0], [8.0, 6.0]],
+        Rect::from_min_max(pos2(10.0, 10.0), pos2(50.0, 40.0)),
+    )];
+    let pixels = full_repaint(|_| {}, &texture(id, checker, options), &primitives);
+
+    let [width, height] = SCREEN_SIZE;
+    for y in 0..height {
+        for x in 0..width {
+            let expected = if !(10..50).contains(&x) || !(10..40).contains(&y) {
+                0
+            } else if (x / 4 + y / 4) % 2 == 0 {
+                WHITE
+            } else {
+                BLACK
+            };
+            assert_eq!(pixels[y * width + x], expected, "pixel [{x}, {y}]");
+        }
+    }
+}
+
+#[test]
+fn packed_textures_sample_like_rgba_textures() {
+    let id = TextureId::User(1);
+    let textures_delta = common::gradient_texture(id, [16, 12]);
+    let mut primitives = vec![common::textured_rect(id, [2.0, 2.0], [40.0, 30.0])];
+    // Magnified and minified, translucent, and over another texture
+    primitives.push(common::textured_rect(id, [30.0, 20.0], [8.0, 6.0]));
+    primitives.push(common::rect(
+        [10.0, 10.0],
+        [30.0, 30.0],
+        Color32::from_rgba_unmultiplied(40, 200, 90, 120),
+    ));
+
+    for options in [TextureOptions::NEAREST, TextureOptions::LINEAR] {
+        let mut textures_delta = textures_delta.clone();
+        textures_delta.set[0].1.options = options;
+        textures_delta.append(common::white_texture());
+
+        let rgba = full_repaint(|_| {}, &textures_delta, &primitives);
+        let packed = full_repaint(
+            |painter| painter.set_texture_storage(egui_euc::TextureStorage::Packed),
+            &textures_delta,
+            &primitives,
+        );
+        assert!(rgba == packed, "{options:?}");
+    }
+}
+
+#[test]
+fn packed_texels_decode_to_linear_color() {
+    use euc::Texture;
+
+    let colors = [
+        Color32::from_rgba_premultiplied(10, 20, 30, 40),
+        Color32::from_rgb(255, 128, 0),
+    ];
+    let texels = colors.map(|c| u32::from_le_bytes(c.to_array()));
+    let mut buffer = euc::Buffer2d::fill([2, 1], 0);
+    buffer.raw_mut().copy_from_slice(&texels);
+    let texture = egui_euc::PackedTexture(buffer);
+
+    for (x, color) in colors.into_iter().enumerate() {
+        assert_eq!(texture.read([x, 0]), egui::Rgba::from(color));
+    }
+}
+
+#[test]
+fn magnified_linear_textures_meet_at_the_mesh_center() {
+    let id = TextureId::User(1);
+    let image = ColorImage::new([2, 1], vec![Color32::RED, Color32::BLUE]);
+    let primitives = [quad(
+        id,
+        [[0.0, 0.0], [200.0, 4.0]],
+        [[0.0, 0.0], [1.0, 1.0]],
+        Rect::EVERYTHING,
+    )];
+    let mut painter = egui_euc::Painter::new();
+    let frame = painter.paint_and_update_textures(
+        &texture(id, image, TextureOptions::LINEAR),
+        &primitives,
+        1.0,
+        [200, 4],
+    );
+    let row: Vec<[u8; 4]> = frame.raw()[..200].iter().map(|p| p.to_le_bytes()).collect();
+
+    // Texel centers sit a quarter of the way in from each edge, so the edges aren't blended
+    assert_eq!(row[0], [255, 0, 0, 255]);
+    assert_eq!(row[199], [0, 0, 255, 255]);
+    assert!(row[..100].iter().all(|[r, _, b, _]| r > b), "{row:?}");
+    assert!(row[100..].iter().all(|[r, _, b, _]| r < b), "{row:?}");
+    // Mirrored around the center
+    for x in 0..100 {
+        let (left, right) = (row[x], row[199 - x]);
+        assert!(left[0].abs_diff(right[2]) <= 1 && left[2].abs_diff(right[0]) <= 1);
+    }
+}
+
+#[test]
+fn oversized_textures_are_stored_downscaled() {
+    let id = TextureId::User(1);
+    let textures_delta = texture(id, single_texel_checker(), TextureOptions::NEAREST);
+
+    let full = draw_square(&textures_delta, id, 32.0, |_| {});
+    assert!(full.contains(&WHITE) && full.contains(&BLACK));
+
+    let downscaled = draw_square(&textures_delta, id, 32.0, |painter| {
+        painter.set_max_texture_size(4)
+    });
+    assert!(downscaled.iter().all(|&pixel| pixel == downscaled[0]));
+    let [r, g, b, a] = downscaled[0].to_le_bytes();
+    assert!(
+        (180..=195).contains(&r) && r == g && g == b && a == 255,
+        "{r} {g} {b} {a}"
+    );
+}
+
+#[test]
+fn user_textures_keep_values_brighter_than_white() {
+    let id = TextureId::User(1);
+    let hdr = euc::Buffer2d::fill([2, 2], egui::Rgba::from_gray(2.0));
+    // Dimmed to a quarter, so twice as bright as the texture is a half
+    let mut mesh = epaint::Mesh::with_texture(id);
+    mesh.add_rect_with_uv(
+        Rect::from_min_max(pos2(0.0, 0.0), pos2(16.0, 16.0)),
+        Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
+        egui::Rgba::from_gray(0.25).into(),
+    );
+    let primitives = [ClippedPrimitive {
+        clip_rect: Rect::EVERYTHING,
+        primitive: epaint::Primitive::Mesh(mesh),
+    }];
+
+    let pixels = full_repaint(
+        |painter| painter.register_user_texture(id, hdr, TextureOptions::NEAREST),
+        &TexturesDelta::default(),
+        &primitives,
+    );
+    let [r, g, b, a] = pixels[8 * SCREEN_SIZE[0] + 8].to_le_bytes();
+    let half = Color32::from(egui::Rgba::from_gray(0.5)).r();
+    assert!(
+        r.abs_diff(half) <= 1 && r == g && g == b && a == 255,
+        "{r} {g} {b} {a}"
+    );
+}
+
+#[test]
+fn bordered_textures_return_the_border_outside() {
+    let id = TextureId::User(1);
+    // UVs from -1 to 2 over 30 pixels, so only the middle 10 pixels are inside the texture
+    let primitives = [quad(
+        id,
+        [[0.0, 0.0], [30.0, 30.0]],
+        [[-1.0, -1.0], [2.0, 2.0]],
+        Rect::EVERYTHING,
+    )];
+    let row = |border: Option<egui::Rgba>| {
+        let pixels = full_repaint(
+            |painter| {
+                let white = euc::Buffer2d::fill([2, 2], egui::Rgba::WHITE);
+                painter.register_user_texture(id, white, TextureOptions::NEAREST);
+                painter.set_texture_border(id, border);
+            },
+            &TexturesDelta::default(),
+            &primitives,
+        );
+        [5, 15, 25].map(|x| pixels[15 * SCREEN_SIZE[0] + x])
+    };
+
+    let blue = u32::from_le_bytes(Color32::BLUE.to_array());
+    assert_eq!(
+        row(Some(egui::Rgba::from(Color32::BLUE))),
+        [blue, WHITE, blue]
+    );
+    // Clamped to the edge again without a border
+    assert_eq!(row(None), [WHITE; 3]);
+}
+
+#[test]
+fn texel_scale_follows_the_mesh_size() {
+    let mesh = |size: f32| {
+        let mut mesh = epaint::Mesh::with_texture(TextureId::User(1));
+        mesh.add_rect_with_uv(
+            Rect::from_min_size(pos2(0.0, 0.0), egui::vec2(size, size)),
+            Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
+            Color32::WHITE,
+        );
+        mesh
+    };
+    assert_eq!(
+        egui_euc::mesh_texel_scale(&mesh(16.0), [8, 8], 1.0),
+        Some(2.0)
+    );
+    assert_eq!(
+        egui_euc::mesh_texel_scale(&mesh(16.0), [8, 8], 0.25),
+        Some(0.5)
+    );
+    assert_eq!(egui_euc::mesh_texel_scale(&mesh(0.0), [8, 8], 1.0), None);
+
+    let mut broken = mesh(16.0);
+    broken.indices.extend([0, 1, 4]);
+    assert_eq!(egui_euc::mesh_texel_scale(&broken, [8, 8], 1.0), None);
+}
+
+#[test]
+fn minified_meshes_use_the_minification_filter() {
+    let id = TextureId::User(1);
+    let textures_delta = texture(id, single_texel_checker(), MIN_LINEAR);
+
+    assert!(is_sharp(&draw_square(&textures_delta, id, 32.0, |_| {})));
+    assert!(!is_sharp(&draw_square(&textures_delta, id, 4.0, |_| {})));
+}
+
+#[test]
+fn lod_bias_moves_the_minification_threshold() {
+    let id = TextureId::User(1);
+    let textures_delta = texture(id, single_texel_checker(), MIN_LINEAR);
+    // Shrunk to three quarters, between the default threshold and one level below it
+    let shrunk = |lod_bias| draw_square(&textures_delta, id, 6.0, |p| p.set_lod_bias(lod_bias));
+
+    assert!(!is_sharp(&shrunk(0.0)));
+    assert!(is_sharp(&shrunk(-1.0)));
+    // Switching earlier blurs even magnified textures
+    let magnified = draw_square(&textures_delta, id, 12.0, |p| p.set_lod_bias(1.0));
+    assert!(!is_sharp(&magnified));
+}
+
+#[test]
+fn linear_uploads_are_not_decoded_again() {
+    let id = TextureId::User(1);
+    let grey = Color32::from_gray(128);
+    let textures_delta = texture(
+        id,
+        ColorImage::new([2, 2], vec![grey; 4]),
+        TextureOptions::NEAREST,
+    );
+    let primitives = [quad(
+        id,
+        [[0.0, 0.0], [8.0, 8.0]],
+        [[0.0, 0.0], [1.0, 1.0]],
+        Rect::EVERYTHING,
+    )];
+    let red = |linear| {
+        let pixels = full_repaint(
+            |p| p.set_texture_linear(id, linear),
+            &textures_delta,
+            &primitives,
+        );
+        pixels[4 * SCREEN_SIZE[0] + 4].to_le_bytes()[0]
+    };
+
+    assert_eq!(red(false), 128);
+    // 128 / 255 taken as linear, which encodes to sRGB 188
+    assert_eq!(
+        red(true),
+        Color32::from(egui::Rgba::from_gray(128.0 / 255.0)).r()
+    );
+}
+
+#[test]
+fn tints_multiply_translucent_texels() {
+    let id = TextureId::User(1);
+    let half_white = euc::Buffer2d::fill([2, 2], egui::Rgba::from_white_alpha(0.5));
+    let tinted = |min: f32, tint: Color32| {
+        let mut mesh = epaint::Mesh::with_texture(id);
+        mesh.add_rect_with_uv(
+            Rect::from_min_size(pos2(min, 0.0), egui::vec2(8.0, 8.0)),
+            Rect::from_min_max(pos2(0.0, 0.0), pos2(1.0, 1.0)),
+            tint,
+        );
+        ClippedPrimitive {
+            clip_rect: Rect::EVERYTHING,
+            primitive: epaint::Primitive::Mesh(mesh),
+        }
+    };
+    let primitives = [
+        tinted(0.0, Color32::RED),
+        tinted(16.0, Color32::from_rgba_unmultiplied(255, 0, 0, 128)),
+    ];
+
+    let pixels = full_repaint(
+        |painter| painter.register_user_texture(id, half_white, TextureOptions::NEAREST),
+        &TexturesDelta::default(),
+        &primitives,
+    );
+    // Half covered red, and a quarter covered red: the alphas multiply, the color stays the tint
+    let [opaque_tint, translucent_tint] =
+        [4, 20].map(|x| pixels[4 * SCREEN_SIZE[0] + x].to_le_bytes());
+    for (pixel, alpha) in [(opaque_tint, 128), (translucent_tint, 64)] {
+        let [r, g, b, a] = pixel;
+        assert!(
+            r >= 254 && g == 0 && b == 0 && a.abs_diff(alpha) <= 1,
+            "{pixel:?}"
+        );
+    }
+}
+
+#[test]
+fn meshes_sample_what_was_painted_into_their_texture() {
+    let id = TextureId::User(1);
+    // The left half of an 8x8 texture
+    let red = common::rect([0.0, 0.0], [4.0, 8.0], Color32::RED);
+    let primitives = [quad(
+        id,
+        [[0.0, 0.0], [16.0, 16.0]],
+        [[0.0, 0.0], [1.0, 1.0]],
+        Rect::EVERYTHING,
+    )];
+    let pixels = full_repaint(
+        |painter| {
+            let white = euc::Buffer2d::fill([8, 8], egui::Rgba::WHITE);
+            painter.register_user_texture(id, white, TextureOptions::NEAREST);
+            painter.register_user_texture(
+                TextureId::default(),
+                euc::Buffer2d::fill([1, 1], egui::Rgba::WHITE),
+                TextureOptions::NEAREST,
+            );
+            painter.paint_into_texture(id, [&red], 1.0).unwrap();
+        },
+        &TexturesDelta::default(),
+        &primitives,
+    );
+
+    let row = &pixels[8 * SCREEN_SIZE[0]..][..16];
+    let red = u32::from_le_bytes([255, 0, 0, 255]);
+    assert_eq!(row, [[red; 8], [WHITE; 8]].concat());
+}
+
+#[test]
+fn srgb_filtering_interpolates_the_encoding() {
+    let id = TextureId::User(1);
+    let image = ColorImage::new([2, 1], vec![Color32::BLACK, Color32::WHITE]);
+    let primitives = [quad(
+        id,
+        [[0.0, 0.0], [200.0, 4.0]],
+        [[0.0, 0.0], [1.0, 1.0]],
+        Rect::EVERYTHING,
+    )];
+    let middle = |srgb_filtering| {
+        let mut painter = egui_euc::Painter::new();
+        painter.set_srgb_filtering(srgb_filtering);
+        let frame = painter.paint_and_update_textures(
+            &texture(id, image.clone(), TextureOptions::LINEAR),
+            &primitives,
+            1.0,
+            [200, 4],
+        );
+        frame.raw()[100].to_le_bytes()[0]
+    };
+
+    // Halfway between the texel centers: linear 0.5, or halfway between the encodings
+    assert!(middle(false).abs_diff(188) <= 2, "{}", middle(false));
+    assert!(middle(true).abs_diff(128) <= 2, "{}", middle(true));
+}
+
+#[test]
+fn reduced_storage_stays_within_its_precision() {
+    use egui_euc::TextureStorage;
+
+    let id = TextureId::User(1);
+    let colors: Vec<_> = (0..16)
+        .map(|i| Color32::from_rgb(i * 17, 255 - i * 13, i * 7 + 40))
+        .collect();
+    let textures_delta = texture(
+        id,
+        ColorImage::new([16, 1], colors.clone()),
+        TextureOptions::NEAREST,
+    );
+    // One pixel per texel
+    let primitives = [quad(
+        id,
+        [[0.0, 0.0], [16.0, 1.0]],
+        [[0.0, 0.0], [1.0, 1.0]],
+        Rect::EVERYTHING,
+    )];
+    let max_error = |storage| {
+        let pixels = full_repaint(
+            |p| p.set_texture_storage(storage),
+            &textures_delta,
+            &primitives,
+        );
+        let pairs = pixels[..16].iter().zip(&colors);
+        pairs
+            .flat_map(|(pixel, color)| {
+                let pixel = pixel.to_le_bytes();
+                (0..3).map(move |i| pixel[i].abs_diff(color.to_array()[i]))
+            })
+            .max()
+            .unwrap()
+    };
+
+    assert_eq!(max_error(TextureStorage::Rgba), 0);
+    // Half a step of 4 bits, and of 5 bits, rounding aside
+    assert!(
+        max_error(TextureStorage::Rgba4444) <= 9,
+        "{}",
+        max_error(TextureStorage::Rgba4444)
+    );
+    assert!(
+        max_error(TextureStorage::Rgb565A8) <= 5,
+        "{}",
+        max_error(TextureStorage::Rgb565A8)
+    );
+}
+
+#[test]
+fn storage_overrides_apply_per_texture() {
+    use egui_euc::TextureStorage;
+
+    let [kept, alpha_only] = [TextureId::User(1), TextureId::User(2)];
+    let red = ColorImage::new([2, 2], vec![Color32::RED; 4]);
+    let mut textures_delta = texture(kept, red.clone(), TextureOptions::NEAREST);
+    textures_delta.append(texture(alpha_only, red, TextureOptions::NEAREST));
+    let primitives = [
+        quad(
+            kept,
+            [[0.0, 0.0], [8.0, 8.0]],
+            [[0.0, 0.0], [1.0, 1.0]],
+            Rect::EVERYTHING,
+        ),
+        quad(
+            alpha_only,
+            [[8.0, 0.0], [16.0, 8.0]],
+            [[0.0, 0.0], [1.0, 1.0]],
+            Rect::EVERYTHING,
+        ),
+    ];
+    let pixels = full_repaint(
+        |p| p.set_texture_storage_of(alpha_only, Some(TextureStorage::Alpha8)),
+        &textures_delta,
+        &primitives,
+    );
+    assert_eq!(
+        pixels[4 * SCREEN_SIZE[0] + 4],
+        u32::from_le_bytes([255, 0, 0, 255])
+    );
+    // Color is dropped, leaving white at the stored alpha
+    assert_eq!(pixels[4 * SCREEN_SIZE[0] + 12], WHITE);
+}
+
+#[test]
+fn settings_apply_to_whole_reuploads_of_existing_textures() {
+    use egui_euc::TextureStorage;
+
+    let id = TextureId::User(1);
+    let red = texture(
+        id,
+        ColorImage::new([2, 2], vec![Color32::RED; 4]),
+        TextureOptions::NEAREST,
+    );
+    let mut painter = egui_euc::Painter::new();
+    let red_pixel = u32::from_le_bytes([255, 0, 0, 255]);
+    assert_eq!(draw_reupload(&mut painter, &red, id)[0], red_pixel);
+
+    painter.set_texture_storage_of(id, Some(TextureStorage::Alpha8));
+    let unchanged = draw_reupload(&mut painter, &TexturesDelta::default(), id);
+    assert_eq!(
+        unchanged[0], red_pixel,
+        "the texture is only stored anew by its next whole upload"
+    );
+    // Same size as before, so only the storage tells the upload apart
+    assert_eq!(draw_reupload(&mut painter, &red, id)[0], WHITE);
+
+    let checker = texture(id, single_texel_checker(), TextureOptions::NEAREST);
+    painter.set_texture_storage_of(id, None);
+    assert!(is_sharp(&draw_reupload(&mut painter, &checker, id)));
+    painter.set_max_texture_size(4);
+    assert!(!is_sharp(&draw_reupload(&mut painter, &checker, id)));
+}
+
+#[test]
+fn remapped_ids_sample_the_translated_texture() {
+    let [theirs, ours] = [TextureId::User(1), TextureId::User(2)];
+    let mut textures_delta = texture(
+        theirs,
+        ColorImage::new([1, 1], vec![Color32::RED]),
+        TextureOptions::NEAREST,
+    );
+    textures_delta.append(texture(
+        ours,
+        ColorImage::new([1, 1], vec![Color32::BLUE]),
+        TextureOptions::NEAREST,
+    ));
+
+    let remap = |id| {
+        if id == TextureId::User(1) {
+            TextureId::User(2)
+        } else {
+            id
+        }
+    };
+    let [unmapped, mapped] = [false, true].map(|remapped| {
+        draw_square(&textures_delta, theirs, 4.0, |p| {
+            if remapped {
+                p.set_texture_id_remap(remap);
+            }
+        })[0]
+    });
+    // Uploads still went to the id they named
+    assert_eq!(unmapped, u32::from_le_bytes([255, 0, 0, 255]));
+    assert_eq!(mapped, u32::from_le_bytes([0, 0, 255, 255]));
+}
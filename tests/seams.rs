@@ -0,0 +1,68 @@
+//! Meshes sharing an edge must tile without gaps, like table cells drawn as separate rectangles.
+
+mod common;
+
+use common::{full_repaint, rect, triangle, white_texture, SCREEN_SIZE};
+use egui::{ClippedPrimitive, Color32};
+
+const LEFT: Color32 = Color32::from_rgb(200, 40, 40);
+const RIGHT: Color32 = Color32::from_rgb(40, 40, 200);
+
+/// Panics if a pixel of `pixels` whose center lies inside `[x0, y0]` to `[x1, y1]` isn't drawn
+/// entirely by one of the two colors.
+fn assert_no_seams(pixels: &[u32], [x0, y0]: [usize; 2], [x1, y1]: [usize; 2]) {
+    let colors = [LEFT, RIGHT].map(|c| u32::from_le_bytes(c.to_array()));
+    for y in y0..y1 {
+        for x in x0..x1 {
+            let pixel = pixels[y * SCREEN_SIZE[0] + x];
+            assert!(
+                colors.contains(&pixel),
+                "pixel [{x}, {y}] is {:?}",
+                pixel.to_le_bytes()
+            );
+        }
+    }
+}
+
+/// Two quads abutting at `x = edge`, over a transparent background.
+fn abutting_quads(edge: f32) -> Vec<ClippedPrimitive> {
+    vec![
+        rect([8.0, 8.0], [edge - 8.0, 32.0], LEFT),
+        rect([edge, 8.0], [48.0 - edge, 32.0], RIGHT),
+    ]
+}
+
+#[test]
+fn abutting_quads_leave_no_seam() {
+    for edge in [20.0, 20.5, 20.3, 31.75] {
+        let pixels = full_repaint(|_| {}, &white_texture(), &abutting_quads(edge));
+        assert_no_seams(&pixels, [8, 8], [48, 40]);
+    }
+}
+
+#[test]
+fn abutting_quads_leave_no_seam_when_scaled() {
+    let mut painter = egui_euc::Painter::new();
+    let pixels = painter.paint_and_update_textures(
+        &white_texture(),
+        &[
+            rect([4.0, 4.0], [6.3, 16.0], LEFT),
+            rect([10.3, 4.0], [13.7, 16.0], RIGHT),
+        ],
+        1.5,
+        SCREEN_SIZE,
+    );
+    // The quads cover [6, 6] to [36, 30] in pixels
+    assert_no_seams(pixels.raw(), [6, 6], [36, 30]);
+}
+
+#[test]
+fn abutting_triangles_leave_no_seam() {
+    // A quad split along its diagonal into two meshes
+    let primitives = [
+        triangle([[8.0, 8.0], [56.0, 8.0], [8.0, 40.0]], LEFT),
+        triangle([[56.0, 8.0], [56.0, 40.0], [8.0, 40.0]], RIGHT),
+    ];
+    let pixels = full_repaint(|_| {}, &white_texture(), &primitives);
+    assert_no_seams(&pixels, [8, 8], [56, 40]);
+}
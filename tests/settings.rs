@@ -0,0 +1,283 @@
+//! Effects of the output settings of `Painter`.
+
+mod common;
+
+use common::{full_repaint, rect, solid_texture, textured_rect, white_texture, SCREEN_SIZE};
+use egui::{Color32, TextureId};
+use egui_euc::BlendOp;
+
+/// Index of the pixel at `[x, y]` in a frame.
+fn at(x: usize, y: usize) -> usize {
+    y * SCREEN_SIZE[0] + x
+}
+
+#[test]
+fn text_gamma_only_applies_to_glyphs() {
+    // Half covered "glyphs", in the font atlas and in a user texture
+    let coverage = Color32::from_rgba_premultiplied(128, 128, 128, 128);
+    let font = TextureId::default();
+    let image = TextureId::User(1);
+    let mut textures_delta = solid_texture(font, [4, 4], coverage);
+    textures_delta.append(solid_texture(image, [4, 4], coverage));
+    let primitives = [
+        textured_rect(font, [0.0, 0.0], [16.0, 16.0]),
+        textured_rect(image, [32.0, 0.0], [16.0, 16.0]),
+    ];
+
+    let alpha = |gamma: f32| {
+        let pixels = full_repaint(|p| p.set_text_gamma(gamma), &textures_delta, &primitives);
+        [at(8, 8), at(40, 8)].map(|i| pixels[i].to_le_bytes()[3])
+    };
+    let [glyph, other] = alpha(1.0);
+    assert_eq!([glyph, other], [128, 128]);
+    let [heavier_glyph, same_other] = alpha(1.4);
+    assert!(heavier_glyph > glyph, "{heavier_glyph} <= {glyph}");
+    assert_eq!(same_other, other);
+}
+
+#[test]
+fn global_opacity_scales_alpha() {
+    let translucent = Color32::from_rgba_unmultiplied(0, 0, 255, 128);
+    let primitives = [
+        rect([0.0, 0.0], [16.0, 16.0], Color32::RED),
+        rect([32.0, 0.0], [16.0, 16.0], translucent),
+        // Translucent over opaque, overlapping the red rect
+        rect([8.0, 0.0], [16.0, 16.0], translucent),
+    ];
+
+    let pixels = full_repaint(|p| p.set_global_opacity(0.5), &white_texture(), &primitives);
+    let [opaque, alone, over] = [at(4, 8), at(40, 8), at(12, 8)].map(|i| pixels[i].to_le_bytes());
+    // Colors are unmultiplied, so only alpha changes
+    assert_eq!(opaque[..3], [255, 0, 0]);
+    assert!(opaque[3].abs_diff(128) <= 1, "{opaque:?}");
+    assert!(alone[2] >= 254, "{alone:?}");
+    assert!(alone[3].abs_diff(64) <= 1, "{alone:?}");
+    // Each layer is faded before blending: 0.5 + 0.25 * (1 - 0.5)
+    assert!(over[3].abs_diff(159) <= 1, "{over:?}");
+}
+
+#[test]
+fn fragment_hook_inverts_colors() {
+    let primitives = [
+        rect([0.0, 0.0], [16.0, 16.0], Color32::RED),
+        rect(
+            [32.0, 0.0],
+            [16.0, 16.0],
+            Color32::from_rgba_unmultiplied(255, 0, 0, 128),
+        ),
+    ];
+    // Inverted within the premultiplied range, which keeps alpha
+    let invert = |c: egui::Rgba| {
+        egui::Rgba::from_rgba_premultiplied(c.a() - c.r(), c.a() - c.g(), c.a() - c.b(), c.a())
+    };
+
+    let pixels = full_repaint(
+        |p| p.set_fragment_hook(invert),
+        &white_texture(),
+        &primitives,
+    );
+    let [opaque, translucent] = [at(8, 8), at(40, 8)].map(|i| pixels[i].to_le_bytes());
+    assert_eq!(opaque, [0, 255, 255, 255]);
+    assert_eq!(translucent[..3], [0, 255, 255]);
+    assert!(translucent[3].abs_diff(128) <= 1, "{translucent:?}");
+}
+
+#[test]
+fn text_gamma_applies_to_added_glyph_pages() {
+    let coverage = Color32::from_rgba_premultiplied(128, 128, 128, 128);
+    let [font, page, image] = [TextureId::default(), TextureId::User(2), TextureId::User(1)];
+    let mut textures_delta = solid_texture(font, [4, 4], coverage);
+    textures_delta.append(solid_texture(page, [4, 4], coverage));
+    textures_delta.append(solid_texture(image, [4, 4], coverage));
+    // Glyphs from two pages next to an image, all in the same frame
+    let primitives = [
+        textured_rect(font, [0.0, 0.0], [16.0, 16.0]),
+        textured_rect(page, [16.0, 0.0], [16.0, 16.0]),
+        textured_rect(image, [32.0, 0.0], [16.0, 16.0]),
+    ];
+
+    let pixels = full_repaint(
+        |p| {
+            p.add_font_texture(page);
+            p.set_text_gamma(1.4);
+        },
+        &textures_delta,
+        &primitives,
+    );
+    let [font, page, image] = [at(8, 8), at(24, 8), at(40, 8)].map(|i| pixels[i].to_le_bytes()[3]);
+    assert!(font > 128, "{font}");
+    assert_eq!(page, font);
+    assert_eq!(image, 128);
+}
+
+#[test]
+fn replacing_matches_blending_for_opaque_scenes() {
+    // Pixel aligned, so there are no anti-aliased edges to blend
+    let opaque = [
+        rect([0.0, 0.0], [64.0, 48.0], Color32::from_rgb(30, 30, 40)),
+        rect([4.0, 4.0], [30.0, 20.0], Color32::from_rgb(200, 80, 20)),
+        rect([20.0, 10.0], [30.0, 30.0], Color32::from_rgb(20, 120, 220)),
+    ];
+    let paint = |blend_op, primitives: &[_]| {
+        full_repaint(|p| p.set_blend_op(blend_op), &white_texture(), primitives)
+    };
+    assert_eq!(
+        paint(BlendOp::Replace, &opaque),
+        paint(BlendOp::Over, &opaque)
+    );
+
+    // Translucent content shows the difference
+    let mut translucent = opaque.to_vec();
+    translucent.push(rect([0.0, 0.0], [8.0, 8.0], Color32::from_black_alpha(128)));
+    let [replaced, blended] = [BlendOp::Replace, BlendOp::Over].map(|op| paint(op, &translucent));
+    assert_eq!(replaced[at(2, 2)].to_le_bytes()[3], 128);
+    assert_eq!(blended[at(2, 2)].to_le_bytes()[3], 255);
+}
+
+/// Coordinates of the pixels of a frame which aren't transparent.
+fn drawn(pixels: &[u32]) -> Vec<[usize; 2]> {
+    (0..pixels.len())
+        .filter(|&i| pixels[i] != 0)
+        .map(|i| [i % SCREEN_SIZE[0], i / SCREEN_SIZE[0]])
+        .collect()
+}
+
+#[test]
+fn viewport_confines_the_frame() {
+    // Covering everything, clip rect included
+    let primitives = [rect([0.0, 0.0], [64.0, 48.0], Color32::RED)];
+    let pixels = full_repaint(
+        |p| p.set_viewport(Some([8, 4, 16, 10])),
+        &white_texture(),
+        &primitives,
+    );
+    let expected: Vec<_> = (4..14).flat_map(|y| (8..24).map(move |x| [x, y])).collect();
+    assert_eq!(drawn(&pixels), expected);
+}
+
+#[test]
+fn logical_offset_scrolls_vertices_and_clip_rects() {
+    let mut clipped = rect([10.0, 10.0], [8.0, 8.0], Color32::RED);
+    // Only the left half of the rect
+    clipped.clip_rect = egui::Rect::from_min_size(egui::pos2(10.0, 10.0), egui::vec2(4.0, 8.0));
+    let pixels = full_repaint(
+        |p| p.set_logical_offset(egui::vec2(3.0, 2.0)),
+        &white_texture(),
+        &[clipped],
+    );
+    let expected: Vec<_> = (8..16).flat_map(|y| (7..11).map(move |x| [x, y])).collect();
+    assert_eq!(drawn(&pixels), expected);
+}
+
+#[test]
+fn color_blindness_transforms() {
+    use egui::Rgba;
+    use egui_euc::{ColorBlindness, PostProcess};
+
+    let kinds = [
+        ColorBlindness::Protanopia,
+        ColorBlindness::Deuteranopia,
+        ColorBlindness::Tritanopia,
+    ];
+    for kind in kinds {
+        for post_process in [PostProcess::ColorBlind(kind), PostProcess::Daltonize(kind)] {
+            // Neutral colors are seen the same by everyone
+            for grey in [
+                Rgba::WHITE,
+                Rgba::from_gray(0.3),
+                Rgba::from_white_alpha(0.5),
+            ] {
+                let out = post_process.apply(grey);
+                let diff = (0..4).map(|i| (out[i] - grey[i]).abs()).fold(0.0, f32::max);
+                assert!(diff < 1e-3, "{post_process:?} {grey:?} -> {out:?}");
+            }
+        }
+    }
+
+    // Red and green look alike without red cones: both become shades of the same yellow
+    let simulate = |c| PostProcess::ColorBlind(ColorBlindness::Protanopia).apply(c);
+    let [red, green] = [Rgba::RED, Rgba::GREEN].map(simulate);
+    let hue = |c: Rgba| c.r() / c.g();
+    assert!(
+        (hue(red) / hue(green) - 1.0).abs() < 0.1,
+        "{red:?} {green:?}"
+    );
+
+    // Daltonization moves red towards blue, which stays visible
+    let correct = |c| PostProcess::Daltonize(ColorBlindness::Protanopia).apply(c);
+    let [red, green] = [Rgba::RED, Rgba::GREEN].map(correct);
+    assert!(red.b() > green.b() + 0.3, "{red:?} {green:?}");
+}
+
+#[test]
+fn wide_pixels_squeeze_content_horizontally() {
+    let mut square = rect([4.0, 4.0], [10.0, 10.0], Color32::RED);
+    // Clipped to its top half, which must squeeze along with it
+    square.clip_rect = egui::Rect::from_min_size(egui::pos2(4.0, 4.0), egui::vec2(10.0, 5.0));
+    let pixels = full_repaint(
+        |p| p.set_pixel_aspect_ratio(2.0),
+        &white_texture(),
+        &[square],
+    );
+    let expected: Vec<_> = (4..9).flat_map(|y| (2..7).map(move |x| [x, y])).collect();
+    assert_eq!(drawn(&pixels), expected);
+}
+
+#[test]
+fn transforms_rotate_and_scale_around_the_pivot() {
+    let bar = [rect([20.0, 10.0], [20.0, 10.0], Color32::RED)];
+    // A quarter turn around the bar's center stands it up
+    let pixels = full_repaint(
+        |p| p.set_transform_around(egui::pos2(30.0, 15.0), std::f32::consts::FRAC_PI_2, 1.0),
+        &white_texture(),
+        &bar,
+    );
+    let expected: Vec<_> = (5..25)
+        .flat_map(|y| (25..35).map(move |x| [x, y]))
+        .collect();
+    assert_eq!(drawn(&pixels), expected);
+
+    // Doubled around its top left corner, which stays in place
+    let pixels = full_repaint(
+        |p| p.set_transform_around(egui::pos2(20.0, 10.0), 0.0, 2.0),
+        &white_texture(),
+        &bar,
+    );
+    let expected: Vec<_> = (10..30)
+        .flat_map(|y| (20..60).map(move |x| [x, y]))
+        .collect();
+    assert_eq!(drawn(&pixels), expected);
+}
+
+#[test]
+fn mirroring_flips_the_whole_frame() {
+    // Asymmetric both ways
+    let primitives = [
+        rect([4.0, 4.0], [10.0, 8.0], Color32::RED),
+        rect([40.0, 30.0], [4.0, 6.0], Color32::BLUE),
+    ];
+    let plain = full_repaint(|_| (), &white_texture(), &primitives);
+    let [width, height] = SCREEN_SIZE;
+    for mirror in [[true, false], [false, true], [true, true]] {
+        let mirrored = full_repaint(
+            |p| p.set_mirror(mirror[0], mirror[1]),
+            &white_texture(),
+            &primitives,
+        );
+        let expected: Vec<u32> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| [x, y]))
+            .map(|[x, y]| {
+                let x = if mirror[0] { width - 1 - x } else { x };
+                let y = if mirror[1] { height - 1 - y } else { y };
+                plain[at(x, y)]
+            })
+            .collect();
+        assert!(mirrored == expected, "mirroring {mirror:?}");
+    }
+    // The red rect moves to the right edge
+    let mirrored = full_repaint(|p| p.set_mirror(true, false), &white_texture(), &primitives);
+    assert_eq!(
+        mirrored[at(55, 8)],
+        u32::from_le_bytes(Color32::RED.to_array())
+    );
+}
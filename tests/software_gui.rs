@@ -0,0 +1,222 @@
+//! Frame-to-frame behaviour of `SoftwareGui`.
+
+use egui_euc::{HeadlessInput, SoftwareGui};
+
+const SCREEN_SIZE: [usize; 2] = [160, 120];
+
+fn label(ctx: &egui::Context) {
+    egui::CentralPanel::default().show(ctx, |ui| ui.label("Hello"));
+}
+
+fn input() -> egui::RawInput {
+    HeadlessInput::new(SCREEN_SIZE, 1.0).build()
+}
+
+#[test]
+fn update_if_changed_skips_identical_frames() {
+    let mut gui = SoftwareGui::new();
+    // egui may lay out the first frames again once it knows the sizes of things
+    for _ in 0..3 {
+        gui.update_if_changed(input(), SCREEN_SIZE, label);
+    }
+    assert!(gui.update_if_changed(input(), SCREEN_SIZE, label).is_none());
+
+    let other = |ctx: &egui::Context| {
+        egui::CentralPanel::default().show(ctx, |ui| ui.label("Goodbye"));
+    };
+    assert!(gui.update_if_changed(input(), SCREEN_SIZE, other).is_some());
+}
+
+#[test]
+fn update_texture_uploads_only_changes() {
+    let parent = egui::Context::default();
+    let blank = egui::ColorImage::new([1, 1], vec![egui::Color32::BLACK]);
+    let mut texture = parent.load_texture("sub-gui", blank, Default::default());
+
+    let mut gui = SoftwareGui::new();
+    assert!(gui.update_texture(
+        &mut texture,
+        Default::default(),
+        input(),
+        SCREEN_SIZE,
+        label
+    ));
+    assert_eq!(texture.size(), SCREEN_SIZE);
+    for _ in 0..3 {
+        gui.update_texture(
+            &mut texture,
+            Default::default(),
+            input(),
+            SCREEN_SIZE,
+            label,
+        );
+    }
+    assert!(!gui.update_texture(
+        &mut texture,
+        Default::default(),
+        input(),
+        SCREEN_SIZE,
+        label
+    ));
+}
+
+#[test]
+fn update_forgets_the_previous_frame() {
+    let mut gui = SoftwareGui::new();
+    for _ in 0..3 {
+        gui.update_if_changed(input(), SCREEN_SIZE, label);
+    }
+    // The host presented whatever update returned, so the next frame must be presented too
+    gui.update(input(), SCREEN_SIZE, label);
+    assert!(gui.update_if_changed(input(), SCREEN_SIZE, label).is_some());
+}
+
+#[test]
+fn measure_leaves_the_context_alone() {
+    let mut gui = SoftwareGui::new();
+    gui.update(input(), SCREEN_SIZE, label);
+    let pass = gui.egui_ctx.cumulative_pass_nr();
+
+    let size = gui.measure(|ui| {
+        ui.label("Hello");
+    });
+    assert!(size.x > 0.0 && size.y > 0.0);
+    assert_eq!(gui.egui_ctx.cumulative_pass_nr(), pass);
+
+    // Measuring the same content again gives the same size
+    let again = gui.measure(|ui| {
+        ui.label("Hello");
+    });
+    assert_eq!(size, again);
+}
+
+#[test]
+fn tooltips_are_painted_over_windows() {
+    let tooltip = egui::Color32::from_rgb(250, 200, 0);
+    let window = egui::Color32::from_rgb(0, 80, 160);
+    let rect = egui::Rect::from_min_size(egui::pos2(40.0, 30.0), egui::vec2(60.0, 40.0));
+    let layer = |order| egui::LayerId::new(order, egui::Id::new("layer"));
+
+    let mut gui = SoftwareGui::new();
+    let image = gui.update(input(), SCREEN_SIZE, |ctx| {
+        // Submitted first, but egui orders layers when tessellating
+        ctx.layer_painter(layer(egui::Order::Tooltip))
+            .rect_filled(rect, 0.0, tooltip);
+        ctx.layer_painter(layer(egui::Order::Middle))
+            .rect_filled(rect.expand(10.0), 0.0, window);
+    });
+
+    let [width, _] = SCREEN_SIZE;
+    assert_eq!(image.pixels[50 * width + 70], tooltip);
+    assert_eq!(image.pixels[25 * width + 70], window);
+}
+
+#[test]
+fn update_layers_splits_by_layer() {
+    let tooltip = egui::Color32::from_rgb(250, 200, 0);
+    let window = egui::Color32::from_rgb(0, 80, 160);
+    let rect = egui::Rect::from_min_size(egui::pos2(40.0, 30.0), egui::vec2(60.0, 40.0));
+    let layer = |order| egui::LayerId::new(order, egui::Id::new("layer"));
+    let uv = egui::Rect::from_min_max(egui::pos2(0.0, 0.0), egui::pos2(1.0, 1.0));
+    let tooltip_image = egui::Rect::from_min_size(egui::pos2(0.0, 0.0), egui::vec2(20.0, 20.0));
+    let window_image = egui::Rect::from_min_size(egui::pos2(130.0, 95.0), egui::vec2(20.0, 20.0));
+
+    let mut gui = SoftwareGui::new();
+    let (tooltips, rest) = gui.update_layers(
+        input(),
+        SCREEN_SIZE,
+        |layer_id| layer_id.order == egui::Order::Tooltip,
+        |ctx| {
+            ctx.layer_painter(layer(egui::Order::Tooltip))
+                .rect_filled(rect, 0.0, tooltip);
+            ctx.layer_painter(layer(egui::Order::Middle)).rect_filled(
+                rect.expand(10.0),
+                0.0,
+                window,
+            );
+
+            // Freed by the same delta that uploads it, but both images still draw with it
+            let image = egui::ColorImage::new([1, 1], vec![egui::Color32::GREEN]);
+            let texture = ctx.load_texture("freed", image, Default::default());
+            ctx.layer_painter(layer(egui::Order::Tooltip)).image(
+                texture.id(),
+                tooltip_image,
+                uv,
+                egui::Color32::WHITE,
+            );
+            ctx.layer_painter(layer(egui::Order::Middle)).image(
+                texture.id(),
+                window_image,
+                uv,
+                egui::Color32::WHITE,
+            );
+        },
+    );
+    assert!(gui.software_render.last_errors().is_empty());
+
+    let [width, _] = SCREEN_SIZE;
+    let (inside, outside) = (50 * width + 70, 25 * width + 70);
+    assert_eq!(tooltips.pixels[inside], tooltip);
+    assert_eq!(tooltips.pixels[outside], egui::Color32::TRANSPARENT);
+    // The window is complete below the tooltip
+    assert_eq!(rest.pixels[inside], window);
+    assert_eq!(rest.pixels[outside], window);
+    assert_eq!(tooltips.pixels[10 * width + 10], egui::Color32::GREEN);
+    assert_eq!(rest.pixels[105 * width + 140], egui::Color32::GREEN);
+}
+
+#[test]
+fn fixed_pixels_per_point_overrides_the_input() {
+    let mut gui = SoftwareGui::new();
+    gui.set_pixels_per_point(Some(2.0));
+    gui.update(input(), SCREEN_SIZE, label);
+    assert_eq!(gui.egui_ctx.pixels_per_point(), 2.0);
+    // Still filling the painted size
+    assert_eq!(gui.egui_ctx.screen_rect().size(), egui::vec2(80.0, 60.0));
+
+    gui.set_pixels_per_point(None);
+    gui.update(input(), SCREEN_SIZE, label);
+    assert_eq!(gui.egui_ctx.pixels_per_point(), 1.0);
+    assert_eq!(gui.egui_ctx.screen_rect().size(), egui::vec2(160.0, 120.0));
+}
+
+#[test]
+fn repaint_delay_follows_what_egui_asked_for() {
+    let mut gui = SoftwareGui::new();
+    for _ in 0..3 {
+        gui.update(input(), SCREEN_SIZE, label);
+    }
+    assert_eq!(gui.repaint_delay(), std::time::Duration::MAX);
+    assert!(!gui.needs_repaint());
+
+    gui.update(input(), SCREEN_SIZE, |ctx| {
+        label(ctx);
+        ctx.request_repaint();
+    });
+    assert!(gui.needs_repaint());
+
+    let later = std::time::Duration::from_secs(1);
+    gui.update(input(), SCREEN_SIZE, |ctx| {
+        label(ctx);
+        ctx.request_repaint_after(later);
+    });
+    assert_eq!(gui.repaint_delay(), later);
+}
+
+#[test]
+fn added_fonts_render_text() {
+    // Only the added font is available
+    let data = egui::FontDefinitions::default()
+        .font_data
+        .into_values()
+        .next()
+        .unwrap();
+    let mut gui = SoftwareGui::new();
+    gui.set_fonts(egui::FontDefinitions::empty());
+    gui.add_font("custom", (*data).clone());
+
+    let image = gui.update(input(), SCREEN_SIZE, label);
+    let background = image.pixels[0];
+    let text = image.pixels.iter().filter(|&&p| p != background).count();
+    assert!(text > 20, "only {text} pixels of text");
+}
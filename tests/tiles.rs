@@ -0,0 +1,55 @@
+//! Tiles reported by `Painter::paint_tiled_damage`.
+
+mod common;
+
+use common::{rect, white_texture, SCREEN_SIZE};
+use egui::{Color32, TexturesDelta};
+use egui_euc::{Painter, TileId};
+
+const TILE_SIZE: [usize; 2] = [16, 16];
+
+#[test]
+fn first_frame_damages_every_tile() {
+    let mut painter = Painter::new();
+    let primitives = [rect([0.0, 0.0], [8.0, 8.0], Color32::RED)];
+    let (_, tiles) =
+        painter.paint_tiled_damage(&white_texture(), &primitives, 1.0, SCREEN_SIZE, TILE_SIZE);
+    // 64 by 48 pixels make 4 by 3 tiles
+    assert_eq!(tiles.len(), 12);
+}
+
+#[test]
+fn only_tiles_of_changed_primitives_are_damaged() {
+    let frame = |color| {
+        [
+            rect([1.0, 1.0], [6.0, 6.0], color),
+            rect([20.0, 20.0], [8.0, 8.0], Color32::GREEN),
+            rect([57.0, 41.0], [6.0, 6.0], color),
+        ]
+    };
+    let mut painter = Painter::new();
+    painter.paint_tiled_damage(
+        &white_texture(),
+        &frame(Color32::RED),
+        1.0,
+        SCREEN_SIZE,
+        TILE_SIZE,
+    );
+    let (buffer, tiles) = painter.paint_tiled_damage(
+        &TexturesDelta::default(),
+        &frame(Color32::BLUE),
+        1.0,
+        SCREEN_SIZE,
+        TILE_SIZE,
+    );
+    let buffer = buffer.raw().to_vec();
+
+    // The tiles in between them are inside their bounding box, but nothing changed there
+    assert_eq!(
+        tiles,
+        [TileId { column: 0, row: 0 }, TileId { column: 3, row: 2 }]
+    );
+
+    let expected = common::full_repaint(|_| {}, &white_texture(), &frame(Color32::BLUE));
+    assert!(buffer == expected);
+}
@@ -0,0 +1,122 @@
+//! Texture uploads spread over frames by `Painter::set_upload_budget`.
+
+mod common;
+
+use common::{full_repaint, gradient_texture, textured_rect, SCREEN_SIZE};
+use egui::{TextureId, TexturesDelta};
+use egui_euc::Painter;
+
+#[test]
+fn split_uploads_match_a_single_upload() {
+    let id = TextureId::User(3);
+    let textures_delta = gradient_texture(id, [32, 24]);
+    let primitives = vec![textured_rect(id, [0.0, 0.0], [64.0, 48.0])];
+
+    let mut painter = Painter::new();
+    // A few rows of the texture per frame
+    painter.set_upload_budget(Some(32 * 4 * 5));
+    painter.paint_diff(&textures_delta, &primitives, 1.0, SCREEN_SIZE);
+
+    let mut frames = 1;
+    while painter.has_pending_uploads() {
+        painter.paint_diff(&TexturesDelta::default(), &primitives, 1.0, SCREEN_SIZE);
+        frames += 1;
+        assert!(frames < 100, "uploads never finished");
+    }
+    assert_eq!(frames, 5, "24 rows at 5 rows per frame take 5 frames");
+
+    // Each chunk must have redrawn the texture, or the retained frame shows it half arrived
+    let split = painter.buffer(0).raw().to_vec();
+    assert!(split == full_repaint(|_| {}, &textures_delta, &primitives));
+}
+
+#[test]
+fn deltas_queue_behind_deferred_ones() {
+    let id = TextureId::User(3);
+    let primitives = vec![textured_rect(id, [0.0, 0.0], [64.0, 48.0])];
+    let first = gradient_texture(id, [32, 24]);
+    let second = common::solid_texture(id, [32, 24], egui::Color32::RED);
+
+    let mut painter = Painter::new();
+    painter.set_upload_budget(Some(32 * 4 * 5));
+    painter.paint_and_update_textures(&first, &primitives, 1.0, SCREEN_SIZE);
+    // The second upload fits the budget on its own, but must not overtake the first
+    painter.set_upload_budget(Some(32 * 4 * 24));
+    let painted = painter.paint_and_update_textures(&second, &primitives, 1.0, SCREEN_SIZE);
+    assert!(painter.has_pending_uploads());
+    assert!(painted.raw() != full_repaint(|_| {}, &second, &primitives).as_slice());
+
+    while painter.has_pending_uploads() {
+        painter.paint_and_update_textures(&TexturesDelta::default(), &primitives, 1.0, SCREEN_SIZE);
+    }
+    let painted =
+        painter.paint_and_update_textures(&TexturesDelta::default(), &primitives, 1.0, SCREEN_SIZE);
+    assert!(painted.raw() == full_repaint(|_| {}, &second, &primitives).as_slice());
+}
+
+/// Paints each of `ids` over the whole screen, one frame each.
+#[cfg(feature = "par")]
+fn paint_each(painter: &mut Painter, ids: &[TextureId]) -> Vec<Vec<u32>> {
+    ids.iter()
+        .map(|&id| {
+            let primitives = [textured_rect(id, [0.0, 0.0], [64.0, 48.0])];
+            let painted = painter.paint_and_update_textures(
+                &TexturesDelta::default(),
+                &primitives,
+                1.0,
+                SCREEN_SIZE,
+            );
+            painted.raw().to_vec()
+        })
+        .collect()
+}
+
+#[cfg(feature = "par")]
+#[test]
+fn parallel_texture_creation_matches_sequential_uploads() {
+    use egui::{epaint, ColorImage, TextureOptions};
+    use egui_euc::PainterError;
+
+    // Enough texels in total for new textures to be decoded on threads
+    let ids: Vec<_> = (0..24).map(TextureId::User).collect();
+    let mut textures_delta = TexturesDelta::default();
+    for &id in &ids {
+        textures_delta
+            .set
+            .extend(gradient_texture(id, [40, 40]).set);
+    }
+    // An update of a texture which the whole delta before it creates
+    let mut update = common::solid_texture(ids[3], [8, 8], egui::Color32::RED)
+        .set
+        .remove(0);
+    update.1.pos = Some([4, 4]);
+    textures_delta.set.insert(10, update);
+    // Errors of deltas which don't create a texture come before later errors of ones which do
+    let (missing, empty) = (TextureId::User(100), TextureId::User(101));
+    let mut missing_update = common::solid_texture(missing, [2, 2], egui::Color32::RED)
+        .set
+        .remove(0);
+    missing_update.1.pos = Some([0, 0]);
+    textures_delta.set.insert(2, missing_update);
+    let empty_image = ColorImage::new([0, 0], Vec::new());
+    let empty_delta = epaint::ImageDelta::full(empty_image, TextureOptions::NEAREST);
+    textures_delta.set.insert(5, (empty, empty_delta));
+
+    let mut parallel = Painter::new();
+    parallel.begin_frame(&textures_delta);
+    parallel.end_frame();
+    let errors = [
+        PainterError::MissingTexture { id: missing },
+        PainterError::EmptyImage { id: empty },
+    ];
+    assert_eq!(parallel.last_errors(), errors);
+
+    // Any budget applies the deltas one at a time
+    let mut sequential = Painter::new();
+    sequential.set_upload_budget(Some(usize::MAX));
+    sequential.begin_frame(&textures_delta);
+    sequential.end_frame();
+    assert_eq!(sequential.last_errors(), errors);
+
+    assert!(paint_each(&mut parallel, &ids) == paint_each(&mut sequential, &ids));
+}
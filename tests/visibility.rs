@@ -0,0 +1,42 @@
+//! `Painter::drew_visible_pixels` reports what a paint drew, not what the buffer holds.
+
+mod common;
+
+use common::{rect, white_texture, SCREEN_SIZE};
+use egui::Color32;
+use egui_euc::Painter;
+use euc::Buffer2d;
+
+#[test]
+fn visible_meshes_count() {
+    let mut painter = Painter::new();
+    let primitives = [rect([4.0, 4.0], [8.0, 8.0], Color32::RED)];
+    painter.paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    assert!(painter.drew_visible_pixels());
+}
+
+#[test]
+fn transparent_meshes_do_not_count() {
+    let mut painter = Painter::new();
+    let primitives = [rect([4.0, 4.0], [8.0, 8.0], Color32::TRANSPARENT)];
+    painter.paint_and_update_textures(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    assert!(!painter.drew_visible_pixels());
+}
+
+#[test]
+fn backgrounds_do_not_count() {
+    let mut painter = Painter::new();
+    let background = Buffer2d::fill(SCREEN_SIZE, u32::MAX);
+    painter.paint_over_background(&background, &white_texture(), &[], 1.0);
+    assert!(!painter.drew_visible_pixels());
+}
+
+#[test]
+fn unchanged_retained_frames_do_not_count() {
+    let mut painter = Painter::new();
+    let primitives = [rect([4.0, 4.0], [8.0, 8.0], Color32::RED)];
+    painter.paint_diff(&white_texture(), &primitives, 1.0, SCREEN_SIZE);
+    assert!(painter.drew_visible_pixels());
+    painter.paint_diff(&Default::default(), &primitives, 1.0, SCREEN_SIZE);
+    assert!(!painter.drew_visible_pixels());
+}
@@ -0,0 +1,15 @@
+//! Canvas output with the `web` feature. Only built for wasm32, where `web_sys::ImageData` can be
+//! constructed; elsewhere this just has to compile, e.g. with
+//! `cargo test --no-run --target wasm32-unknown-unknown --features web`.
+
+#![cfg(all(feature = "web", target_arch = "wasm32"))]
+
+use euc::Buffer2d;
+use wasm_bindgen::JsValue;
+
+#[test]
+fn image_data_helper_type_checks() {
+    let convert: fn(&Buffer2d<u32>) -> Result<web_sys::ImageData, JsValue> =
+        egui_euc::buffer_to_web_image_data;
+    let _ = convert;
+}